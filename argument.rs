@@ -0,0 +1,98 @@
+use crate::search::find_model;
+use crate::{evaluate_at, FrameFamily, Model, Proposition, System, World};
+
+/// How a conclusion must follow from premises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consequence {
+    /// At every world where all the premises hold, so does the conclusion.
+    Local,
+    /// In every model where the premises hold at every world, so does the conclusion.
+    Global,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validity {
+    Valid,
+    /// The premises hold but the conclusion fails at `world` of `model`: at that world for local
+    /// consequence, and throughout `model` for global consequence.
+    Invalid { model: Model, world: World },
+}
+
+impl Validity {
+    pub fn is_valid(&self) -> bool {
+        *self == Validity::Valid
+    }
+}
+
+/// An argument from `premises` to `conclusion` in a modal `system`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModalArgument {
+    pub premises: Vec<Proposition>,
+    pub conclusion: Proposition,
+    pub system: System,
+    pub consequence: Consequence,
+    /// The largest countermodel `check` looks for, as with `find_countermodel`.
+    pub max_worlds: usize,
+}
+
+impl ModalArgument {
+    /// A local-consequence argument checked on models of up to 3 worlds.
+    pub fn new(premises: Vec<Proposition>, conclusion: Proposition, system: System) -> ModalArgument {
+        ModalArgument { premises, conclusion, system, consequence: Consequence::Local, max_worlds: 3 }
+    }
+
+    /// Searches the frames of the system for a smallest countermodel. Like `is_valid_in`, `Valid` only
+    /// means there's none within `max_worlds` worlds.
+    pub fn check(&self) -> Validity {
+        let family = FrameFamily::System(self.system);
+        let mut props = self.premises.clone();
+        props.push(self.conclusion.clone());
+        let holds = |model: &Model, world: World, prop: &Proposition| evaluate_at(model, world, prop);
+        let countermodel = match self.consequence {
+            Consequence::Local => find_model(&props, family, self.max_worlds, |model| {
+                model.worlds().find(|world| self.premises.iter().all(|premise| holds(model, *world, premise)) && !holds(model, *world, &self.conclusion))
+            }),
+            Consequence::Global => find_model(&props, family, self.max_worlds, |model| {
+                if !model.worlds().all(|world| self.premises.iter().all(|premise| holds(model, world, premise))) {
+                    return None;
+                }
+                model.worlds().find(|world| !holds(model, *world, &self.conclusion))
+            }),
+        };
+        match countermodel {
+            Some((model, world)) => Validity::Invalid { model, world },
+            None => Validity::Valid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    fn argument(premises: &[&str], conclusion: &str, system: System) -> ModalArgument {
+        ModalArgument::new(premises.iter().map(|premise| parse_formula(premise).unwrap()).collect(), parse_formula(conclusion).unwrap(), system)
+    }
+
+    #[test]
+    fn checks_arguments_in_k() {
+        assert!(argument(&["□P"], "□P", System::K).check().is_valid());
+        match argument(&["◇P"], "P", System::K).check() {
+            Validity::Invalid { model, world } => {
+                assert!(evaluate_at(&model, world, &parse_formula("◇P").unwrap()));
+                assert!(!model.holds(world, 'P'));
+            }
+            Validity::Valid => panic!("◇P doesn't entail P"),
+        }
+    }
+
+    #[test]
+    fn distinguishes_local_from_global_consequence() {
+        let mut necessitation = argument(&["P"], "□P", System::K);
+        assert!(!necessitation.check().is_valid());
+        necessitation.consequence = Consequence::Global;
+        assert!(necessitation.check().is_valid());
+        assert!(argument(&["□P"], "P", System::T).check().is_valid());
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::rpn::apply;
+use crate::{to_rpn, Proposition, Token};
+
+/// A set of formulas with their subformulas interned across the whole set, so a subformula shared by
+/// several formulas (or repeated within one) is evaluated once per assignment.
+#[derive(Debug, Clone)]
+pub struct FormulaBatch {
+    // Distinct subformulas as an operator and the indices of its operands, operands first.
+    nodes: Vec<(Token, Vec<usize>)>,
+    roots: Vec<usize>,
+}
+
+impl FormulaBatch {
+    pub fn new(props: &[Proposition]) -> FormulaBatch {
+        let mut nodes = Vec::new();
+        let mut interned: HashMap<(Token, Vec<usize>), usize> = HashMap::new();
+        let roots = props
+            .iter()
+            .map(|prop| {
+                let mut stack: Vec<usize> = Vec::new();
+                for token in to_rpn(prop) {
+                    let operands = stack.split_off(stack.len() - token.arity());
+                    let node = *interned.entry((token, operands)).or_insert_with_key(|node| {
+                        nodes.push(node.clone());
+                        nodes.len() - 1
+                    });
+                    stack.push(node);
+                }
+                stack[0]
+            })
+            .collect();
+        FormulaBatch { nodes, roots }
+    }
+
+    /// The number of distinct subformulas across the batch, each of which is evaluated once per assignment.
+    pub fn distinct_subformulas(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Evaluates every formula of the batch under `env`, in order, with the propositional semantics of `eval_rpn`.
+    pub fn evaluate(&self, env: &HashMap<char, bool>) -> Result<Vec<bool>> {
+        let mut values: Vec<bool> = Vec::with_capacity(self.nodes.len());
+        let mut operand_values = [false; 2];
+        for (token, operands) in &self.nodes {
+            for (value, operand) in operand_values.iter_mut().zip(operands) {
+                *value = values[*operand];
+            }
+            values.push(apply(*token, &operand_values[..operands.len()], env)?);
+        }
+        Ok(self.roots.iter().map(|root| values[*root]).collect())
+    }
+}
+
+pub fn evaluate_batch(props: &[Proposition], env: &HashMap<char, bool>) -> Result<Vec<bool>> {
+    FormulaBatch::new(props).evaluate(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval_rpn, parse_formula};
+
+    #[test]
+    fn shares_subformulas_across_an_axiom_batch() {
+        let axioms: Vec<Proposition> = ["P → (Q → P)", "(P → (Q → R)) → ((P → Q) → (P → R))", "(¬Q → ¬P) → (P → Q)", "(P → Q) → (¬Q → ¬P)"]
+            .iter()
+            .map(|axiom| parse_formula(axiom).unwrap())
+            .collect();
+        let batch = FormulaBatch::new(&axioms);
+        let total: usize = axioms.iter().map(|axiom| to_rpn(axiom).len()).sum();
+        assert!(batch.distinct_subformulas() < total - 10);
+
+        for bits in 0..8 {
+            let env = HashMap::from([('P', bits & 1 != 0), ('Q', bits & 2 != 0), ('R', bits & 4 != 0)]);
+            let separately: Vec<bool> = axioms.iter().map(|axiom| eval_rpn(&to_rpn(axiom), &env).unwrap()).collect();
+            assert_eq!(batch.evaluate(&env).unwrap(), separately);
+        }
+    }
+
+    #[test]
+    fn evaluates_mixed_batches() {
+        let props = [parse_formula("P ∧ Q").unwrap(), parse_formula("P ∨ Q").unwrap(), parse_formula("P ∧ Q").unwrap()];
+        let env = HashMap::from([('P', true), ('Q', false)]);
+        assert_eq!(evaluate_batch(&props, &env), Ok(vec![false, true, false]));
+        assert_eq!(FormulaBatch::new(&props).distinct_subformulas(), 4);
+    }
+}
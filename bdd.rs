@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use crate::{atoms, Atom, AtomOrder, Connective, Proposition};
+
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+// The level of the two terminals, below every atom.
+const LEAF: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    level: usize,
+    low: usize,
+    high: usize,
+}
+
+/// A reduced ordered binary decision diagram of a propositional formula, with the formula's atoms tested
+/// in the order of `order()`, first atom at the root.
+#[derive(Debug, Clone)]
+pub struct Bdd {
+    order: Vec<char>,
+    // The terminals `FALSE` and `TRUE` first. Nodes no longer reachable from `root` are left in place.
+    nodes: Vec<Node>,
+    unique: HashMap<Node, usize>,
+    root: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorderStrategy {
+    /// Moves each atom in turn, most frequently tested first, through every level by swapping it with
+    /// its neighbours, and leaves it wherever the diagram had the fewest nodes (Rudell's sifting).
+    Sifting,
+}
+
+impl Bdd {
+    /// The BDD of `prop` under the sorted atom order, or `None` if it has modal operators. Nominals are
+    /// treated like atoms.
+    pub fn new(prop: &Proposition) -> Option<Bdd> {
+        Bdd::with_order(prop, &AtomOrder::Sorted)
+    }
+
+    pub fn with_order(prop: &Proposition, order: &AtomOrder) -> Option<Bdd> {
+        let mut bdd = Bdd::empty(order.arrange(&atoms(prop)));
+        bdd.root = bdd.build(prop)?;
+        Some(bdd)
+    }
+
+    pub fn order(&self) -> &[char] {
+        &self.order
+    }
+
+    /// The number of decision nodes reachable from the root, not counting the two terminals.
+    pub fn node_count(&self) -> usize {
+        self.reachable().len()
+    }
+
+    fn reachable(&self) -> Vec<usize> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut pending = vec![self.root];
+        let mut reachable = Vec::new();
+        while let Some(node) = pending.pop() {
+            if node <= TRUE || std::mem::replace(&mut seen[node], true) {
+                continue;
+            }
+            reachable.push(node);
+            pending.extend([self.nodes[node].low, self.nodes[node].high]);
+        }
+        reachable
+    }
+
+    /// The formula's value under `env`. Atoms missing from `env` are false.
+    pub fn evaluate(&self, env: &HashMap<char, bool>) -> bool {
+        let mut node = self.root;
+        while node > TRUE {
+            let Node { level, low, high } = self.nodes[node];
+            node = if env.get(&self.order[level]).copied().unwrap_or(false) { high } else { low };
+        }
+        node == TRUE
+    }
+
+    /// Reorders the atoms to shrink the diagram, returning the node counts before and after. The order
+    /// found is a local optimum, not necessarily the best one.
+    pub fn reorder(&mut self, strategy: ReorderStrategy) -> (usize, usize) {
+        let before = self.node_count();
+        match strategy {
+            ReorderStrategy::Sifting => self.sift(),
+        }
+        (before, self.node_count())
+    }
+
+    fn sift(&mut self) {
+        let mut tested: HashMap<char, usize> = HashMap::new();
+        for node in self.reachable() {
+            *tested.entry(self.order[self.nodes[node].level]).or_default() += 1;
+        }
+        let mut atoms = self.order.clone();
+        atoms.sort_by_key(|atom| std::cmp::Reverse(tested.get(atom).copied().unwrap_or(0)));
+        for atom in atoms {
+            let mut level = self.order.iter().position(|other| *other == atom).expect("atom is in the order");
+            let (mut fewest, mut best) = (self.node_count(), level);
+            // Down to the bottom, then up to the top, then back to wherever the diagram was smallest.
+            while level + 1 < self.order.len() {
+                self.swap(level);
+                level += 1;
+                if self.node_count() < fewest {
+                    (fewest, best) = (self.node_count(), level);
+                }
+            }
+            while level > 0 {
+                self.swap(level - 1);
+                level -= 1;
+                if self.node_count() < fewest {
+                    (fewest, best) = (self.node_count(), level);
+                }
+            }
+            while level < best {
+                self.swap(level);
+                level += 1;
+            }
+        }
+    }
+
+    // Exchanges the atoms at `level` and `level + 1` in place. Every node keeps its index and the
+    // function it stands for, so only the nodes at the two levels change.
+    fn swap(&mut self, level: usize) {
+        let at = |bdd: &Bdd, level: usize| (0..bdd.nodes.len()).filter(|node| bdd.nodes[*node].level == level).collect::<Vec<usize>>();
+        let (upper, lower) = (at(self, level), at(self, level + 1));
+        let cofactors = |bdd: &Bdd, node: usize| {
+            let Node { level: node_level, low, high } = bdd.nodes[node];
+            if node_level == level + 1 {
+                (low, high)
+            } else {
+                (node, node)
+            }
+        };
+        // Each upper node's four outcomes, by its own atom and then the lower one.
+        let outcomes: Vec<(usize, [usize; 4])> = upper
+            .iter()
+            .map(|node| {
+                let ((low_low, low_high), (high_low, high_high)) = (cofactors(self, self.nodes[*node].low), cofactors(self, self.nodes[*node].high));
+                (*node, [low_low, low_high, high_low, high_high])
+            })
+            .collect();
+        for node in upper.iter().chain(&lower) {
+            self.unique.remove(&self.nodes[*node]);
+        }
+        for node in lower {
+            self.nodes[node].level = level;
+            self.unique.insert(self.nodes[node], node);
+        }
+        // Upper nodes that don't test the lower atom just move down a level. They go first, so the nodes
+        // built for the others share them.
+        let (independent, dependent): (Vec<_>, Vec<_>) = outcomes.into_iter().partition(|(_, [low_low, low_high, high_low, high_high])| low_low == low_high && high_low == high_high);
+        for (node, _) in independent {
+            self.nodes[node].level = level + 1;
+            self.unique.insert(self.nodes[node], node);
+        }
+        for (node, [low_low, low_high, high_low, high_high]) in dependent {
+            let low = self.make(Node { level: level + 1, low: low_low, high: high_low });
+            let high = self.make(Node { level: level + 1, low: low_high, high: high_high });
+            self.nodes[node] = Node { level, low, high };
+            self.unique.insert(self.nodes[node], node);
+        }
+        self.order.swap(level, level + 1);
+    }
+
+    fn empty(order: Vec<char>) -> Bdd {
+        let terminal = Node { level: LEAF, low: FALSE, high: FALSE };
+        Bdd { order, nodes: vec![terminal, Node { high: TRUE, ..terminal }], unique: HashMap::new(), root: FALSE }
+    }
+
+    fn build(&mut self, prop: &Proposition) -> Option<usize> {
+        let node = match prop {
+            Proposition::Atom(Atom::True) => TRUE,
+            Proposition::Atom(Atom::False) => FALSE,
+            Proposition::Atom(Atom::Variable(atom) | Atom::Nominal(atom)) => self.variable(*atom),
+            Proposition::Connective(Connective::And(left, right)) => self.combine(|a, b| a && b, left, right)?,
+            Proposition::Connective(Connective::Or(left, right)) => self.combine(|a, b| a || b, left, right)?,
+            Proposition::Connective(Connective::IfThen(left, right)) => self.combine(|a, b| !a || b, left, right)?,
+            Proposition::Connective(Connective::Iff(left, right)) => self.combine(|a, b| a == b, left, right)?,
+            Proposition::Connective(Connective::Not(prop)) => {
+                let node = self.build(prop)?;
+                self.apply(|a, _| !a, node, node)
+            }
+            Proposition::Parenthesised(prop) => self.build(prop)?,
+            Proposition::Connective(_) => return None,
+        };
+        Some(node)
+    }
+
+    fn combine(&mut self, op: fn(bool, bool) -> bool, left: &Proposition, right: &Proposition) -> Option<usize> {
+        let (left, right) = (self.build(left)?, self.build(right)?);
+        Some(self.apply(op, left, right))
+    }
+
+    fn variable(&mut self, atom: char) -> usize {
+        let level = self.order.iter().position(|other| *other == atom).expect("atom is in the order");
+        self.make(Node { level, low: FALSE, high: TRUE })
+    }
+
+    fn make(&mut self, node: Node) -> usize {
+        if node.low == node.high {
+            return node.low;
+        }
+        if let Some(index) = self.unique.get(&node) {
+            return *index;
+        }
+        self.nodes.push(node);
+        self.unique.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn apply(&mut self, op: fn(bool, bool) -> bool, left: usize, right: usize) -> usize {
+        self.apply_memo(op, left, right, &mut HashMap::new())
+    }
+
+    fn apply_memo(&mut self, op: fn(bool, bool) -> bool, left: usize, right: usize, memo: &mut HashMap<(usize, usize), usize>) -> usize {
+        if left <= TRUE && right <= TRUE {
+            return if op(left == TRUE, right == TRUE) { TRUE } else { FALSE };
+        }
+        if let Some(node) = memo.get(&(left, right)) {
+            return *node;
+        }
+        let level = self.nodes[left].level.min(self.nodes[right].level);
+        let cofactors = |bdd: &Bdd, node: usize| {
+            let Node { level: node_level, low, high } = bdd.nodes[node];
+            if node_level == level {
+                (low, high)
+            } else {
+                (node, node)
+            }
+        };
+        let ((left_low, left_high), (right_low, right_high)) = (cofactors(self, left), cofactors(self, right));
+        let low = self.apply_memo(op, left_low, right_low, memo);
+        let high = self.apply_memo(op, left_high, right_high, memo);
+        let node = self.make(Node { level, low, high });
+        memo.insert((left, right), node);
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval_rpn, parse_formula, to_rpn};
+
+    // (A ∧ B) ∨ (C ∧ D) ∨ (E ∧ F), whose BDD is linear when each pair is adjacent and exponential when
+    // every left-hand atom comes first.
+    fn pairs() -> Proposition {
+        let pair = |left, right| Proposition::and(Proposition::variable(left), Proposition::variable(right));
+        Proposition::disjunction([pair('A', 'B'), pair('C', 'D'), pair('E', 'F')])
+    }
+
+    #[test]
+    fn agrees_with_the_formula() {
+        let prop = parse_formula("(P → Q) ↔ ¬(R ∧ P)").unwrap();
+        let bdd = Bdd::new(&prop).unwrap();
+        let tokens = to_rpn(&prop);
+        for bits in 0..8 {
+            let env = HashMap::from([('P', bits & 1 != 0), ('Q', bits & 2 != 0), ('R', bits & 4 != 0)]);
+            assert_eq!(bdd.evaluate(&env), eval_rpn(&tokens, &env).unwrap());
+        }
+        assert_eq!(Bdd::new(&parse_formula("P ∨ ¬P").unwrap()).unwrap().node_count(), 0);
+        assert!(Bdd::new(&parse_formula("◇P").unwrap()).is_none());
+    }
+
+    #[test]
+    fn sifting_shrinks_a_badly_ordered_bdd() {
+        let prop = pairs();
+        assert_eq!(Bdd::new(&prop).unwrap().node_count(), 6);
+        let mut bdd = Bdd::with_order(&prop, &AtomOrder::Custom(vec!['A', 'C', 'E', 'B', 'D', 'F'])).unwrap();
+        let original = bdd.clone();
+        let (before, after) = bdd.reorder(ReorderStrategy::Sifting);
+        assert_eq!(before, 14);
+        assert_eq!(after, 6);
+        assert_eq!(after, bdd.node_count());
+        for bits in 0..64 {
+            let env: HashMap<char, bool> = "ABCDEF".chars().enumerate().map(|(index, atom)| (atom, bits & (1 << index) != 0)).collect();
+            assert_eq!(bdd.evaluate(&env), original.evaluate(&env));
+        }
+    }
+
+    #[test]
+    fn swapping_neighbouring_atoms_keeps_the_function() {
+        let prop = parse_formula("(P ∧ Q) ∨ (¬R ↔ S) ∨ (Q → T)").unwrap();
+        let original = Bdd::new(&prop).unwrap();
+        let mut bdd = original.clone();
+        for level in [0, 2, 1, 3, 0, 3, 2] {
+            bdd.swap(level);
+            for bits in 0..32 {
+                let env: HashMap<char, bool> = "PQRST".chars().enumerate().map(|(index, atom)| (atom, bits & (1 << index) != 0)).collect();
+                assert_eq!(bdd.evaluate(&env), original.evaluate(&env));
+            }
+        }
+        assert_eq!(bdd.order(), ['S', 'Q', 'R', 'P', 'T']);
+        assert_eq!(Bdd::with_order(&prop, &AtomOrder::Custom(bdd.order().to_vec())).unwrap().node_count(), bdd.node_count());
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Model, Proposition, World};
+
+/// The Hennessy–Milner characteristic formula of `model` at `world` up to modal depth `depth`.
+///
+/// It fixes the value of every atom of the model at `world`, asserts `◇` of each successor's
+/// characteristic formula at one depth less, and `□` of their disjunction. So it holds at exactly
+/// those pointed models that are `depth`-bisimilar to this one over the model's atoms.
+pub fn characteristic_formula(model: &Model, world: World, depth: usize) -> Proposition {
+    characteristic(model, &model.atoms(), world, depth)
+}
+
+fn characteristic(model: &Model, atoms: &BTreeSet<char>, world: World, depth: usize) -> Proposition {
+    let valuation = atoms.iter().map(|atom| {
+        let atom_prop = Proposition::variable(*atom);
+        if model.holds(world, *atom) {
+            atom_prop
+        } else {
+            Proposition::negation(atom_prop)
+        }
+    });
+    if depth == 0 {
+        return Proposition::conjunction(valuation);
+    }
+    let mut successors: Vec<Proposition> = Vec::new();
+    for next in model.successors(world) {
+        let successor = characteristic(model, atoms, next, depth - 1);
+        if !successors.contains(&successor) {
+            successors.push(successor);
+        }
+    }
+    let possibilities = successors.iter().map(|successor| Proposition::possibly(successor.clone()));
+    let necessity = Proposition::necessarily(Proposition::disjunction(successors.iter().cloned()));
+    Proposition::conjunction(valuation.chain(possibilities).chain([necessity]))
+}
+
+/// The least modal depth of a formula true at one pointed model and false at the other, or `None`
+/// if the two are bisimilar and so agree on every formula of the basic modal language.
+///
+/// Worlds of both models are refined together: at depth 0 they're split by valuation, and at depth
+/// k + 1 also by which depth-k classes their successors fall into. The pointed models are
+/// k-bisimilar until the first depth at which they land in different classes.
+pub fn distinguishing_depth(first: &Model, first_world: World, second: &Model, second_world: World) -> Option<usize> {
+    refine_until_distinguished(first, first_world, second, second_world, None)
+}
+
+/// Whether the two pointed models satisfy the same modal formulas of modal depth at most `depth`, i.e.
+/// are `depth`-bisimilar. This only refines the partition `depth` times, where `bisimilar` may need more.
+pub fn modally_equivalent_up_to(first: &Model, first_world: World, second: &Model, second_world: World, depth: usize) -> bool {
+    refine_until_distinguished(first, first_world, second, second_world, Some(depth)).is_none()
+}
+
+// The first refinement round separating the two worlds, giving up after `bound` rounds if there is one.
+fn refine_until_distinguished(first: &Model, first_world: World, second: &Model, second_world: World, bound: Option<usize>) -> Option<usize> {
+    let atoms: BTreeSet<char> = first.atoms().union(&second.atoms()).copied().collect();
+    let models = [first, second];
+    let nodes: Vec<(usize, World)> = models.iter().enumerate().flat_map(|(side, model)| model.worlds().map(move |world| (side, world))).collect();
+    let valuation = |(side, world): (usize, World)| -> Vec<bool> { atoms.iter().map(|atom| models[side].holds(world, *atom)).collect() };
+    let mut classes = number_classes(&nodes, valuation);
+    let mut depth = 0;
+    loop {
+        if classes.get(&(0, first_world)) != classes.get(&(1, second_world)) {
+            return Some(depth);
+        }
+        if bound == Some(depth) {
+            return None;
+        }
+        let refined = number_classes(&nodes, |(side, world)| {
+            let successors: BTreeSet<usize> = models[side].successors(world).map(|next| classes[&(side, next)]).collect();
+            (classes[&(side, world)], successors)
+        });
+        if refined.values().max() == classes.values().max() {
+            return None;
+        }
+        classes = refined;
+        depth += 1;
+    }
+}
+
+pub fn bisimilar(first: &Model, first_world: World, second: &Model, second_world: World) -> bool {
+    distinguishing_depth(first, first_world, second, second_world).is_none()
+}
+
+/// The quotient of `model` by its largest bisimulation: each class of bisimilar worlds becomes its least
+/// world, with the union of the class's edges and its names. Every world keeps its modal theory.
+pub fn bisimulation_contraction(model: &Model) -> Model {
+    let atoms = model.atoms();
+    let nodes: Vec<(usize, World)> = model.worlds().map(|world| (0, world)).collect();
+    let mut classes = number_classes(&nodes, |(_, world)| -> Vec<bool> { atoms.iter().map(|atom| model.holds(world, *atom)).collect() });
+    loop {
+        let refined = number_classes(&nodes, |(_, world)| {
+            let successors: BTreeSet<usize> = model.successors(world).map(|next| classes[&(0, next)]).collect();
+            (classes[&(0, world)], successors)
+        });
+        if refined.values().max() == classes.values().max() {
+            break;
+        }
+        classes = refined;
+    }
+    let mut representatives: BTreeMap<usize, World> = BTreeMap::new();
+    for ((_, world), class) in &classes {
+        representatives.entry(*class).or_insert(*world);
+    }
+    let representative = |world: World| representatives[&classes[&(0, world)]];
+    let mut contracted = Model::new();
+    for world in representatives.values() {
+        let true_atoms: Vec<char> = atoms.iter().copied().filter(|atom| model.holds(*world, *atom)).collect();
+        contracted.add_world(*world, &true_atoms);
+    }
+    for world in model.worlds() {
+        for next in model.successors(world) {
+            contracted.add_edge(representative(world), representative(next));
+        }
+    }
+    for (name, world) in model.names() {
+        contracted.name_world(name, representative(world));
+    }
+    contracted
+}
+
+/// Whether the modalities collapse at `world`, i.e. `□φ ↔ φ` holds there for every formula φ, as at a
+/// world that sees exactly itself. That's so just when `world` sees some world and every world it sees
+/// is bisimilar to it: `□⊥` rules out the first, and a formula telling a successor apart the second.
+pub fn has_modal_collapse(model: &Model, world: World) -> bool {
+    model.successors(world).next().is_some() && model.successors(world).all(|next| bisimilar(model, next, model, world))
+}
+
+// Numbers the distinct signatures of `nodes`, so nodes share a class exactly when their signatures agree.
+fn number_classes<S: Ord>(nodes: &[(usize, World)], signature: impl Fn((usize, World)) -> S) -> BTreeMap<(usize, World), usize> {
+    let mut numbers: BTreeMap<S, usize> = BTreeMap::new();
+    nodes
+        .iter()
+        .map(|node| {
+            let next = numbers.len();
+            (*node, *numbers.entry(signature(*node)).or_insert(next))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{evaluate_at, parse_formula};
+
+    #[test]
+    fn characteristic_formula_holds_exactly_at_bisimilar_worlds() {
+        // 0 → 1, with P true only at 1.
+        let mut model = Model::new();
+        model.add_world(0, &[]);
+        model.add_world(1, &['P']);
+        model.add_edge(0, 1);
+        let formula = characteristic_formula(&model, 0, 2);
+        assert!(evaluate_at(&model, 0, &formula));
+        assert!(!evaluate_at(&model, 1, &formula));
+
+        // Duplicating the successor gives a bisimilar world.
+        let mut duplicated = model.clone();
+        duplicated.add_world(2, &['P']);
+        duplicated.add_edge(0, 2);
+        assert!(evaluate_at(&duplicated, 0, &formula));
+
+        // A successor without P breaks bisimilarity at depth 1.
+        let mut extended = model.clone();
+        extended.add_world(2, &[]);
+        extended.add_edge(0, 2);
+        assert!(!evaluate_at(&extended, 0, &formula));
+    }
+
+    #[test]
+    fn characteristic_formula_distinguishes_deeper_structure() {
+        // Both roots see a P-world, but only the first root's successor sees anything.
+        let mut model = Model::new();
+        model.add_world(1, &['P']);
+        model.add_world(2, &['P']);
+        model.add_world(4, &['P']);
+        model.add_edge(0, 1);
+        model.add_edge(1, 2);
+        model.add_edge(3, 4);
+        assert!(evaluate_at(&model, 3, &characteristic_formula(&model, 0, 1)));
+        assert!(!evaluate_at(&model, 3, &characteristic_formula(&model, 0, 2)));
+    }
+
+    #[test]
+    fn finds_the_least_distinguishing_depth() {
+        let mut long = Model::new();
+        long.add_edge(0, 1);
+        long.add_edge(1, 2);
+        let mut short = Model::new();
+        short.add_edge(0, 1);
+        assert_eq!(distinguishing_depth(&long, 0, &short, 0), Some(2));
+        assert_eq!(distinguishing_depth(&long, 1, &short, 0), None);
+
+        short.add_world(1, &['P']);
+        assert_eq!(distinguishing_depth(&long, 0, &short, 0), Some(1));
+        assert_eq!(distinguishing_depth(&long, 0, &short, 1), Some(0));
+    }
+
+    #[test]
+    fn bounds_equivalence_by_depth() {
+        let mut long = Model::new();
+        long.add_edge(0, 1);
+        long.add_edge(1, 2);
+        let mut short = Model::new();
+        short.add_edge(0, 1);
+        assert!(modally_equivalent_up_to(&long, 0, &short, 0, 1));
+        assert!(!modally_equivalent_up_to(&long, 0, &short, 0, 2));
+        assert!(modally_equivalent_up_to(&long, 1, &short, 0, 5));
+    }
+
+    #[test]
+    fn contracts_bisimilar_worlds() {
+        let mut model = Model::new();
+        model.add_world(0, &['P']);
+        model.add_world(1, &['P']);
+        model.add_world(2, &[]);
+        model.add_edge(0, 1);
+        model.add_edge(1, 0);
+        model.add_edge(0, 2);
+        model.add_edge(1, 2);
+        let contracted = bisimulation_contraction(&model);
+        assert_eq!(contracted.worlds().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(contracted.successors(0).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(bisimilar(&model, 1, &contracted, 0));
+    }
+
+    #[test]
+    fn detects_modal_collapse() {
+        let mut model = Model::new();
+        model.add_world(0, &['P']);
+        model.add_edge(0, 0);
+        model.add_world(1, &['P']);
+        model.add_edge(1, 0);
+        model.add_edge(2, 0);
+        model.add_edge(2, 2);
+        model.add_world(3, &[]);
+        assert!(has_modal_collapse(&model, 0));
+        // World 1 sees only a copy of itself.
+        assert!(has_modal_collapse(&model, 1));
+        // World 2 lacks P but sees a P-world, and □⊥ holds at world 3, which sees nothing.
+        assert!(!has_modal_collapse(&model, 2));
+        assert!(!has_modal_collapse(&model, 3));
+        for world in 0..2 {
+            for formula in ["P", "◇P", "□¬P", "◇□P"] {
+                let prop = parse_formula(formula).unwrap();
+                assert_eq!(evaluate_at(&model, world, &Proposition::necessarily(prop.clone())), evaluate_at(&model, world, &prop));
+            }
+        }
+    }
+
+    #[test]
+    fn unravelled_cycles_are_bisimilar() {
+        let mut fixed_point = Model::new();
+        fixed_point.add_world(0, &['P']);
+        fixed_point.add_edge(0, 0);
+        let mut cycle = Model::new();
+        cycle.add_world(0, &['P']);
+        cycle.add_world(1, &['P']);
+        cycle.add_edge(0, 1);
+        cycle.add_edge(1, 0);
+        assert!(bisimilar(&fixed_point, 0, &cycle, 1));
+        cycle.add_edge(1, 2);
+        assert!(!bisimilar(&fixed_point, 0, &cycle, 0));
+    }
+}
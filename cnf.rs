@@ -0,0 +1,236 @@
+use std::collections::BTreeSet;
+
+use crate::rewrite::fold;
+use crate::{to_nnf, Atom, Connective, Proposition, TransformOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    pub atom: char,
+    pub positive: bool,
+}
+
+impl Literal {
+    /// The atom, negated unless the literal is positive.
+    pub fn to_proposition(&self) -> Proposition {
+        let atom = Proposition::variable(self.atom);
+        if self.positive {
+            atom
+        } else {
+            Proposition::negation(atom)
+        }
+    }
+}
+
+/// A disjunction of literals, sorted and without repeats. The empty clause is false.
+pub type Clause = Vec<Literal>;
+
+/// Converts a propositional formula to conjunctive normal form by distributing `∨` over `∧`, which can
+/// take exponentially many clauses. Tautological clauses are dropped, so `⊤` has no clauses and `⊥` has
+/// only the empty one. Nominals are treated like atoms; formulas with modal operators have no CNF.
+pub fn to_cnf(prop: &Proposition) -> Option<Vec<Clause>> {
+    to_cnf_with(prop, TransformOptions::default())
+}
+
+/// `to_cnf`, recursing only as deep as `options` allows.
+pub fn to_cnf_with(prop: &Proposition, options: TransformOptions) -> Option<Vec<Clause>> {
+    let mut clauses = fold(prop, true, options, &cnf_operands, &cnf)?;
+    clauses.sort();
+    clauses.dedup();
+    Some(clauses)
+}
+
+/// The conjunction of `clauses`, as a formula built from literals with `¬`, `∨` and `∧`.
+pub fn from_cnf(clauses: &[Clause]) -> Proposition {
+    Proposition::conjunction(clauses.iter().map(|clause| Proposition::disjunction(clause.iter().map(Literal::to_proposition))))
+}
+
+/// Each occurrence of an atom or nominal in `to_nnf(prop)`, from left to right, as a literal: negative
+/// where it sits under an odd number of `¬`, counting those negation normal form leaves outside `K_a`,
+/// `D`, `C_G`, `X`, `Y` and `□→`. That's its sign in `polarity_map`, except that the antecedent of a
+/// `□→`, mixed there, keeps the sign of the negations around it. Negation normal form spells `↔` out
+/// with both sides twice over, so their occurrences come twice over as well.
+pub fn literals(prop: &Proposition) -> impl Iterator<Item = Literal> {
+    let nnf = to_nnf(prop);
+    let mut literals = Vec::new();
+    let mut pending = vec![(&nnf, true)];
+    while let Some((prop, positive)) = pending.pop() {
+        match prop {
+            Proposition::Atom(Atom::Variable(atom) | Atom::Nominal(atom)) => literals.push(Literal { atom: *atom, positive }),
+            Proposition::Connective(Connective::Not(negated)) => pending.push((negated, !positive)),
+            _ => pending.extend(prop.operands().into_iter().rev().map(|operand| (operand, positive))),
+        }
+    }
+    literals.into_iter()
+}
+
+/// Shrinks a CNF before it's handed to a solver, by dropping tautological clauses, unit propagation,
+/// pure-literal elimination and subsumption, until none of them applies. The result is satisfiable
+/// exactly when `clauses` is, but need not be equivalent to it: it loses the atoms these steps fix.
+/// An unsatisfiable CNF comes out as the single empty clause.
+pub fn simplify_cnf(clauses: &[Clause]) -> Vec<Clause> {
+    let mut clauses: Vec<Clause> = clauses
+        .iter()
+        .map(|clause| {
+            let mut clause = clause.clone();
+            clause.sort();
+            clause.dedup();
+            clause
+        })
+        .filter(|clause| !clause.windows(2).any(|pair| pair[0].atom == pair[1].atom))
+        .collect();
+    loop {
+        if clauses.iter().any(Vec::is_empty) {
+            return vec![vec![]];
+        }
+        let literals: BTreeSet<Literal> = clauses.iter().flatten().copied().collect();
+        let unit = clauses.iter().find(|clause| clause.len() == 1).map(|clause| clause[0]);
+        let pure = || literals.iter().find(|literal| !literals.contains(&Literal { positive: !literal.positive, ..**literal })).copied();
+        match unit.or_else(pure) {
+            // Making the literal true satisfies its clauses and falsifies its negation everywhere else.
+            Some(literal) => {
+                clauses.retain(|clause| !clause.contains(&literal));
+                for clause in &mut clauses {
+                    clause.retain(|other| other.atom != literal.atom);
+                }
+            }
+            None => {
+                clauses.sort();
+                clauses.dedup();
+                let subsumed = |clause: &Clause| clauses.iter().any(|other| other != clause && other.iter().all(|literal| clause.contains(literal)));
+                let kept: Vec<Clause> = clauses.iter().filter(|clause| !subsumed(clause)).cloned().collect();
+                // Dropping a clause may leave a literal pure, so go round again if any went.
+                if kept.len() == clauses.len() {
+                    return clauses;
+                }
+                clauses = kept;
+            }
+        }
+    }
+}
+
+// The subformulas whose clauses make up those of `prop` (when `positive`, or of its negation
+// otherwise), each with the sign they're wanted in, in the order `cnf` consumes them.
+fn cnf_operands(prop: &Proposition, positive: bool) -> Vec<(&Proposition, bool)> {
+    match prop {
+        Proposition::Connective(Connective::And(left, right) | Connective::Or(left, right)) => vec![(left, positive), (right, positive)],
+        Proposition::Connective(Connective::IfThen(left, right)) => vec![(left, !positive), (right, positive)],
+        Proposition::Connective(Connective::Iff(left, right)) => vec![(left, true), (left, false), (right, true), (right, false)],
+        Proposition::Connective(Connective::Not(prop)) => vec![(prop, !positive)],
+        Proposition::Parenthesised(prop) => vec![(prop, positive)],
+        _ => vec![],
+    }
+}
+
+// The clauses of `prop` when `positive`, and of its negation otherwise, given those of its
+// `cnf_operands`.
+fn cnf(prop: &Proposition, positive: bool, operands: Vec<Option<Vec<Clause>>>) -> Option<Vec<Clause>> {
+    let mut operands = operands.into_iter().collect::<Option<Vec<_>>>()?.into_iter();
+    let mut next = || operands.next().expect("missing operand");
+    let clauses = match prop {
+        Proposition::Atom(Atom::True) if positive => vec![],
+        Proposition::Atom(Atom::False) if !positive => vec![],
+        Proposition::Atom(Atom::True | Atom::False) => vec![vec![]],
+        Proposition::Atom(Atom::Variable(atom) | Atom::Nominal(atom)) => vec![vec![Literal { atom: *atom, positive }]],
+        Proposition::Connective(Connective::And(..)) if positive => both(next(), next()),
+        Proposition::Connective(Connective::Or(..) | Connective::IfThen(..)) if positive => either(next(), next()),
+        Proposition::Connective(Connective::And(..)) => either(next(), next()),
+        Proposition::Connective(Connective::Or(..) | Connective::IfThen(..)) => both(next(), next()),
+        Proposition::Connective(Connective::Iff(..)) => {
+            let (left_true, left_false, right_true, right_false) = (next(), next(), next(), next());
+            if positive {
+                both(either(left_false, right_true), either(left_true, right_false))
+            } else {
+                both(either(left_true, right_true), either(left_false, right_false))
+            }
+        }
+        Proposition::Connective(Connective::Not(_)) | Proposition::Parenthesised(_) => next(),
+        Proposition::Connective(_) => return None,
+    };
+    Some(clauses)
+}
+
+fn both(mut left: Vec<Clause>, right: Vec<Clause>) -> Vec<Clause> {
+    left.extend(right);
+    left
+}
+
+// Distributes the disjunction of two CNFs into one, dropping the tautologies it produces.
+fn either(left: Vec<Clause>, right: Vec<Clause>) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    for left_clause in &left {
+        for right_clause in &right {
+            let mut clause: Clause = left_clause.iter().chain(right_clause).copied().collect();
+            clause.sort();
+            clause.dedup();
+            if !clause.windows(2).any(|pair| pair[0].atom == pair[1].atom) {
+                clauses.push(clause);
+            }
+        }
+    }
+    clauses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{is_consistent, parse_formula};
+
+    fn literal(atom: char, positive: bool) -> Literal {
+        Literal { atom, positive }
+    }
+
+    #[test]
+    fn converts_to_clauses() {
+        let clauses = to_cnf(&parse_formula("(P ∧ Q) ∨ ¬R").unwrap()).unwrap();
+        assert_eq!(clauses, vec![vec![literal('P', true), literal('R', false)], vec![literal('Q', true), literal('R', false)]]);
+        assert_eq!(to_cnf(&parse_formula("P ∨ ¬P").unwrap()), Some(vec![]));
+        assert_eq!(to_cnf(&parse_formula("¬(P → P)").unwrap()), Some(vec![vec![literal('P', false)], vec![literal('P', true)]]));
+        assert_eq!(to_cnf(&parse_formula("□P").unwrap()), None);
+    }
+
+    #[test]
+    fn lists_literal_occurrences_in_order() {
+        let listed: Vec<Literal> = literals(&parse_formula("P ∧ ¬Q ∧ P").unwrap()).collect();
+        assert_eq!(listed, vec![literal('P', true), literal('Q', false), literal('P', true)]);
+        assert_eq!(listed.iter().filter(|literal| literal.atom == 'P').count(), 2);
+        // A `¬` left outside an operator without a dual still flips the atoms under it.
+        let listed: Vec<Literal> = literals(&parse_formula("¬K_a P ∧ ¬(P □→ ¬Q)").unwrap()).collect();
+        assert_eq!(listed, vec![literal('P', false), literal('P', false), literal('Q', true)]);
+
+        // Negations are pushed through to the atoms first.
+        let listed: Vec<Literal> = literals(&parse_formula("¬(P → □(Q ∨ ¬R))").unwrap()).collect();
+        assert_eq!(listed, vec![literal('P', true), literal('Q', false), literal('R', true)]);
+        assert_eq!(literals(&parse_formula("P ↔ Q").unwrap()).count(), 4);
+        assert_eq!(literals(&Proposition::constant(true)).count(), 0);
+    }
+
+    #[test]
+    fn round_trips_clauses() {
+        let clauses = vec![vec![literal('P', false), literal('Q', true)], vec![literal('R', true)]];
+        assert_eq!(from_cnf(&clauses).to_string(), "(¬P ∨ Q) ∧ R");
+        assert_eq!(to_cnf(&from_cnf(&clauses)), Some(clauses));
+    }
+
+    #[test]
+    fn simplifies_clauses_preserving_satisfiability() {
+        let [p, q, r, s] = ['P', 'Q', 'R', 'S'].map(|atom| move |positive| literal(atom, positive));
+        // P and then Q propagate, satisfying every clause they occur in.
+        let clauses = vec![vec![p(true)], vec![p(false), q(true)], vec![p(true), r(true)], vec![q(false), r(true), s(true)], vec![r(false), s(false)], vec![s(true), r(true)]];
+        let simplified = simplify_cnf(&clauses);
+        assert_eq!(simplified, vec![vec![r(false), s(false)], vec![r(true), s(true)]]);
+
+        let subsumed = vec![vec![p(true), q(true)], vec![r(true), p(true), q(true)], vec![p(false), q(false)], vec![p(false), r(false)], vec![q(false), r(true)]];
+        assert_eq!(simplify_cnf(&subsumed).len(), 4);
+        // Only the subsumed clause has ¬R, so once it goes R is pure, and then so are P and Q.
+        let purified = vec![vec![p(true), q(true)], vec![p(true), q(true), r(false)], vec![p(false), r(true)], vec![q(false), r(true)]];
+        assert_eq!(simplify_cnf(&purified), Vec::<Clause>::new());
+
+        let contradictory = vec![vec![p(true)], vec![p(false), q(true)], vec![q(false)]];
+        assert_eq!(simplify_cnf(&contradictory), vec![vec![]]);
+        assert_eq!(simplify_cnf(&[vec![p(true), p(false)]]), Vec::<Clause>::new());
+
+        for instance in [clauses, subsumed, purified, contradictory] {
+            assert_eq!(is_consistent(&[from_cnf(&simplify_cnf(&instance))]), is_consistent(&[from_cnf(&instance)]));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{entails, is_consistent, Proposition};
+
+/// What a set of assumptions settles about a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Judgement {
+    /// The formula holds under every assignment satisfying the assumptions.
+    True,
+    /// The formula fails under every assignment satisfying the assumptions.
+    False,
+    /// The assumptions leave atoms free in a way that decides the formula both ways.
+    Unknown,
+    /// No assignment satisfies the assumptions, so they settle nothing.
+    Inconsistent,
+}
+
+/// A set of assumed formulas that queries are evaluated under, for working through a proof step by step.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    assumptions: Vec<Proposition>,
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context::default()
+    }
+
+    pub fn assume(&mut self, prop: Proposition) {
+        self.assumptions.push(prop);
+    }
+
+    pub fn assumptions(&self) -> &[Proposition] {
+        &self.assumptions
+    }
+
+    pub fn evaluate(&self, prop: &Proposition) -> Judgement {
+        evaluate_under(&self.assumptions, prop)
+    }
+}
+
+/// Whether `prop` is true, false or undetermined in every model of `assumptions`. When the assumptions fix
+/// every atom of `prop` this is just its value; when they leave some free it's `True` or `False` only if
+/// they entail `prop` or its negation.
+pub fn evaluate_under(assumptions: &[Proposition], prop: &Proposition) -> Judgement {
+    if !is_consistent(assumptions) {
+        Judgement::Inconsistent
+    } else if entails(assumptions, prop) {
+        Judgement::True
+    } else if entails(assumptions, &Proposition::negation(prop.clone())) {
+        Judgement::False
+    } else {
+        Judgement::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    #[test]
+    fn evaluates_under_accumulated_assumptions() {
+        let formula = |source| parse_formula(source).unwrap();
+        let mut context = Context::new();
+        context.assume(formula("P → Q"));
+        assert_eq!(context.evaluate(&formula("Q")), Judgement::Unknown);
+        assert_eq!(context.evaluate(&formula("P ∧ ¬Q")), Judgement::False);
+        context.assume(formula("P"));
+        assert_eq!(context.evaluate(&formula("Q")), Judgement::True);
+        assert_eq!(context.evaluate(&formula("R ∨ ¬Q")), Judgement::Unknown);
+        context.assume(formula("¬Q"));
+        assert_eq!(context.evaluate(&formula("Q")), Judgement::Inconsistent);
+    }
+}
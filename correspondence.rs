@@ -0,0 +1,305 @@
+use std::fmt;
+
+use crate::{atoms, polarity_map, to_nnf, Atom, Connective, Polarity, Proposition};
+
+/// Whether `prop` is a Sahlqvist formula of the basic modal language (`□`, `◇` and the Boolean
+/// connectives), and so corresponds to a first-order condition on frames and is canonical. The
+/// recognised shapes are the Sahlqvist implications `A → B`, where the consequent `B` is positive and
+/// the antecedent `A` is built up from boxed atoms `□…□p`, negative formulas and constants with `∧`,
+/// `∨` and `◇`; and what's made of those with `□`, `∧`, and `∨` between disjuncts sharing no atoms.
+/// Positive formulas count as `⊤ → B`, and `¬A` as `A → ⊥`.
+pub fn is_sahlqvist(prop: &Proposition) -> bool {
+    is_basic(prop) && sahlqvist(prop)
+}
+
+/// A first-order condition on the accessibility relation `R` of a frame, over variables for worlds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FoCondition {
+    True,
+    False,
+    /// `R(x, y)`: `y` is accessible from `x`.
+    Related(FoVariable, FoVariable),
+    Equal(FoVariable, FoVariable),
+    And(Box<FoCondition>, Box<FoCondition>),
+    Or(Box<FoCondition>, Box<FoCondition>),
+    Implies(Box<FoCondition>, Box<FoCondition>),
+    ForAll(FoVariable, Box<FoCondition>),
+    Exists(FoVariable, Box<FoCondition>),
+}
+
+/// A variable for a world, the `n`th of an endless supply. The first 26 are written as letters, `x`,
+/// `y`, `z`, `u` and so on, and the rest as `x26`, `x27`, ….
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FoVariable(pub usize);
+
+impl fmt::Display for FoVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match LETTERS.chars().nth(self.0) {
+            Some(letter) => write!(f, "{}", letter),
+            None => write!(f, "x{}", self.0),
+        }
+    }
+}
+
+const LETTERS: &str = "xyzuvwstabcdefghijklmnopqr";
+
+impl fmt::Display for FoCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |condition: &FoCondition| match condition {
+            FoCondition::And(..) | FoCondition::Or(..) | FoCondition::Implies(..) => format!("({})", condition),
+            _ => condition.to_string(),
+        };
+        match self {
+            FoCondition::True => write!(f, "⊤"),
+            FoCondition::False => write!(f, "⊥"),
+            FoCondition::Related(from, to) => write!(f, "R({}, {})", from, to),
+            FoCondition::Equal(left, right) => write!(f, "{} = {}", left, right),
+            FoCondition::And(left, right) => write!(f, "{} ∧ {}", operand(left), operand(right)),
+            FoCondition::Or(left, right) => write!(f, "{} ∨ {}", operand(left), operand(right)),
+            FoCondition::Implies(left, right) => write!(f, "{} → {}", operand(left), operand(right)),
+            FoCondition::ForAll(variable, body) => write!(f, "∀{} {}", variable, operand(body)),
+            FoCondition::Exists(variable, body) => write!(f, "∃{} {}", variable, operand(body)),
+        }
+    }
+}
+
+/// The first-order frame condition `prop` corresponds to: `prop` is valid on a frame exactly when the
+/// condition holds of its relation, as `□P → P` does on the reflexive frames, `∀x R(x, x)`. Covers the
+/// Sahlqvist implications whose antecedent is a conjunction of boxed atoms `□…□p`, each atom boxed
+/// once, and plain positive formulas, by substituting the least valuation making the antecedent true
+/// into the standard translation of the consequent. Other formulas, Sahlqvist or not, give `None`.
+pub fn frame_correspondent(prop: &Proposition) -> Option<FoCondition> {
+    if !is_sahlqvist(prop) {
+        return None;
+    }
+    let (antecedent, consequent) = match unparenthesised(prop) {
+        Proposition::Connective(Connective::IfThen(antecedent, consequent)) => (&**antecedent, &**consequent),
+        prop if signed(prop, Polarity::Positive) => (&Proposition::Atom(Atom::True), prop),
+        _ => return None,
+    };
+    let mut boxed: Vec<(char, usize)> = Vec::new();
+    let mut pending = vec![antecedent];
+    while let Some(conjunct) = pending.pop() {
+        match unparenthesised(conjunct) {
+            Proposition::Atom(Atom::True) => {}
+            Proposition::Connective(Connective::And(left, right)) => pending.extend([&**left, &**right]),
+            conjunct => match boxed_atom(conjunct) {
+                Some((atom, boxes)) if !boxed.iter().any(|(seen, _)| *seen == atom) => boxed.push((atom, boxes)),
+                _ => return None,
+            },
+        }
+    }
+    let mut variables = (0..).map(FoVariable);
+    let root = variables.next().unwrap();
+    // p holds exactly at the worlds its boxes reach from the root, and nowhere if it isn't boxed.
+    let mut least = |atom: char, world: FoVariable, variables: &mut dyn Iterator<Item = FoVariable>| match boxed.iter().find(|(boxed, _)| *boxed == atom) {
+        Some((_, boxes)) => reaches(root, world, *boxes, variables),
+        None => FoCondition::False,
+    };
+    let translated = translate(&to_nnf(consequent), root, &mut variables, &mut least);
+    Some(simplified(FoCondition::ForAll(root, Box::new(translated))))
+}
+
+// The standard translation of the negation normal form `prop` at the world `world`, with `valuation`
+// translating each atom at a world.
+fn translate(
+    prop: &Proposition,
+    world: FoVariable,
+    variables: &mut dyn Iterator<Item = FoVariable>,
+    valuation: &mut dyn FnMut(char, FoVariable, &mut dyn Iterator<Item = FoVariable>) -> FoCondition,
+) -> FoCondition {
+    let mut binary = |left: &Proposition, right: &Proposition, variables: &mut dyn Iterator<Item = FoVariable>| {
+        (Box::new(translate(left, world, variables, valuation)), Box::new(translate(right, world, variables, valuation)))
+    };
+    match prop {
+        Proposition::Atom(Atom::True) => FoCondition::True,
+        Proposition::Atom(Atom::False) => FoCondition::False,
+        Proposition::Atom(Atom::Variable(atom)) => valuation(*atom, world, variables),
+        Proposition::Connective(Connective::And(left, right)) => {
+            let (left, right) = binary(left, right, variables);
+            FoCondition::And(left, right)
+        }
+        Proposition::Connective(Connective::Or(left, right)) => {
+            let (left, right) = binary(left, right, variables);
+            FoCondition::Or(left, right)
+        }
+        Proposition::Connective(Connective::Necessarily(prop)) => {
+            let next = variables.next().expect("an endless supply of variables");
+            let body = translate(prop, next, variables, valuation);
+            FoCondition::ForAll(next, Box::new(FoCondition::Implies(Box::new(FoCondition::Related(world, next)), Box::new(body))))
+        }
+        Proposition::Connective(Connective::Possibly(prop)) => {
+            let next = variables.next().expect("an endless supply of variables");
+            let body = translate(prop, next, variables, valuation);
+            FoCondition::Exists(next, Box::new(FoCondition::And(Box::new(FoCondition::Related(world, next)), Box::new(body))))
+        }
+        _ => unreachable!("positive formulas of the basic language have only ∧, ∨, □ and ◇ in negation normal form"),
+    }
+}
+
+// `to` is reached from `from` in exactly `steps` steps of `R`.
+fn reaches(from: FoVariable, to: FoVariable, steps: usize, variables: &mut dyn Iterator<Item = FoVariable>) -> FoCondition {
+    match steps {
+        0 => FoCondition::Equal(to, from),
+        1 => FoCondition::Related(from, to),
+        _ => {
+            let middle = variables.next().expect("an endless supply of variables");
+            let rest = reaches(middle, to, steps - 1, variables);
+            FoCondition::Exists(middle, Box::new(FoCondition::And(Box::new(FoCondition::Related(from, middle)), Box::new(rest))))
+        }
+    }
+}
+
+// Folds away constants and repeated conjuncts, and the existentials `∃z (φ ∧ z = t)` as `φ[z/t]`.
+fn simplified(condition: FoCondition) -> FoCondition {
+    use FoCondition::*;
+    match condition {
+        And(left, right) => match (simplified(*left), simplified(*right)) {
+            (False, _) | (_, False) => False,
+            (True, other) | (other, True) => other,
+            (left, right) if left == right => left,
+            (left, right) => And(Box::new(left), Box::new(right)),
+        },
+        Or(left, right) => match (simplified(*left), simplified(*right)) {
+            (True, _) | (_, True) => True,
+            (False, other) | (other, False) => other,
+            (left, right) if left == right => left,
+            (left, right) => Or(Box::new(left), Box::new(right)),
+        },
+        Implies(left, right) => match (simplified(*left), simplified(*right)) {
+            (False, _) | (_, True) => True,
+            (True, right) => right,
+            (left, right) => Implies(Box::new(left), Box::new(right)),
+        },
+        ForAll(variable, body) => match simplified(*body) {
+            True => True,
+            body => ForAll(variable, Box::new(body)),
+        },
+        Exists(variable, body) => match simplified(*body) {
+            False => False,
+            And(left, right) => match (*left, *right) {
+                (Equal(bound, other), rest) | (rest, Equal(bound, other)) if bound == variable && other != variable => simplified(renamed(rest, variable, other)),
+                (Equal(other, bound), rest) | (rest, Equal(other, bound)) if bound == variable && other != variable => simplified(renamed(rest, variable, other)),
+                (left, right) => Exists(variable, Box::new(And(Box::new(left), Box::new(right)))),
+            },
+            body => Exists(variable, Box::new(body)),
+        },
+        condition => condition,
+    }
+}
+
+// `condition` with the variable `from` replaced by `to`. The translation never reuses a variable, so
+// nothing can be captured.
+fn renamed(condition: FoCondition, from: FoVariable, to: FoVariable) -> FoCondition {
+    use FoCondition::*;
+    let rename = |variable: FoVariable| if variable == from { to } else { variable };
+    let boxed = |condition: Box<FoCondition>| Box::new(renamed(*condition, from, to));
+    match condition {
+        Related(left, right) => Related(rename(left), rename(right)),
+        Equal(left, right) => Equal(rename(left), rename(right)),
+        And(left, right) => And(boxed(left), boxed(right)),
+        Or(left, right) => Or(boxed(left), boxed(right)),
+        Implies(left, right) => Implies(boxed(left), boxed(right)),
+        ForAll(variable, body) => ForAll(variable, boxed(body)),
+        Exists(variable, body) => Exists(variable, boxed(body)),
+        condition => condition,
+    }
+}
+
+fn is_basic(prop: &Proposition) -> bool {
+    let mut pending = vec![prop];
+    while let Some(prop) = pending.pop() {
+        match prop {
+            Proposition::Atom(Atom::Nominal(_)) => return false,
+            Proposition::Atom(_)
+            | Proposition::Parenthesised(_)
+            | Proposition::Connective(
+                Connective::And(..) | Connective::Or(..) | Connective::IfThen(..) | Connective::Iff(..) | Connective::Not(_) | Connective::Possibly(_) | Connective::Necessarily(_),
+            ) => pending.extend(prop.operands()),
+            Proposition::Connective(_) => return false,
+        }
+    }
+    true
+}
+
+fn sahlqvist(prop: &Proposition) -> bool {
+    match unparenthesised(prop) {
+        Proposition::Connective(Connective::Necessarily(prop)) => sahlqvist(prop),
+        Proposition::Connective(Connective::And(left, right)) => sahlqvist(left) && sahlqvist(right),
+        Proposition::Connective(Connective::Or(left, right)) => sahlqvist(left) && sahlqvist(right) && atoms(left).is_disjoint(&atoms(right)),
+        Proposition::Connective(Connective::IfThen(antecedent, consequent)) => sahlqvist_antecedent(antecedent) && signed(consequent, Polarity::Positive),
+        Proposition::Connective(Connective::Not(antecedent)) => sahlqvist_antecedent(antecedent),
+        prop => signed(prop, Polarity::Positive),
+    }
+}
+
+fn sahlqvist_antecedent(prop: &Proposition) -> bool {
+    if signed(prop, Polarity::Negative) || boxed_atom(prop).is_some() {
+        return true;
+    }
+    match unparenthesised(prop) {
+        Proposition::Connective(Connective::And(left, right) | Connective::Or(left, right)) => sahlqvist_antecedent(left) && sahlqvist_antecedent(right),
+        Proposition::Connective(Connective::Possibly(prop)) => sahlqvist_antecedent(prop),
+        _ => false,
+    }
+}
+
+// The atom and the number of boxes in front of it, if `prop` is `□…□p`.
+fn boxed_atom(prop: &Proposition) -> Option<(char, usize)> {
+    match unparenthesised(prop) {
+        Proposition::Atom(Atom::Variable(atom)) => Some((*atom, 0)),
+        Proposition::Connective(Connective::Necessarily(prop)) => boxed_atom(prop).map(|(atom, boxes)| (atom, boxes + 1)),
+        _ => None,
+    }
+}
+
+// Whether every atom of `prop` occurs with `polarity`.
+fn signed(prop: &Proposition, polarity: Polarity) -> bool {
+    polarity_map(prop).values().all(|signed| *signed == polarity)
+}
+
+fn unparenthesised(prop: &Proposition) -> &Proposition {
+    match prop {
+        Proposition::Parenthesised(prop) => unparenthesised(prop),
+        _ => prop,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_enriched_formula, parse_formula};
+
+    #[test]
+    fn recognises_sahlqvist_formulas() {
+        for axiom in ["□P → P", "□P → □□P", "P → □◇P", "□P → ◇P", "◇P → □◇P", "◇□P → □◇P", "□(□P → P)", "◇P ∨ (□Q → Q)", "¬(◇□P ∧ ¬P)"] {
+            assert!(is_sahlqvist(&parse_formula(axiom).unwrap()), "{}", axiom);
+        }
+        // McKinsey's and Löb's axioms are the textbook non-Sahlqvist formulas.
+        for axiom in ["□◇P → ◇□P", "□(□P → P) → □P", "(□P → P) ∨ (□P → P)", "□(P ∨ Q) → ◇P"] {
+            assert!(!is_sahlqvist(&parse_formula(axiom).unwrap()), "{}", axiom);
+        }
+        assert!(!is_sahlqvist(&parse_enriched_formula("□P → @i P").unwrap()));
+    }
+
+    #[test]
+    fn computes_frame_correspondents() {
+        let correspondent = |axiom| frame_correspondent(&parse_formula(axiom).unwrap()).map(|condition| condition.to_string());
+        assert_eq!(correspondent("□P → P").as_deref(), Some("∀x R(x, x)"));
+        assert_eq!(correspondent("□P → □□P").as_deref(), Some("∀x ∀y (R(x, y) → ∀z (R(y, z) → R(x, z)))"));
+        assert_eq!(correspondent("P → □◇P").as_deref(), Some("∀x ∀y (R(x, y) → R(y, x))"));
+        assert_eq!(correspondent("□P → ◇P").as_deref(), Some("∀x ∃y R(x, y)"));
+        let seriality = frame_correspondent(&Proposition::possibly(Proposition::constant(true))).unwrap();
+        assert_eq!(seriality.to_string(), "∀x ∃y R(x, y)");
+        assert_eq!(correspondent("□□P → □P").as_deref(), Some("∀x ∀y (R(x, y) → ∃z (R(x, z) ∧ R(z, y)))"));
+        assert_eq!(correspondent("□◇P → ◇□P"), None);
+        // Sahlqvist, but with a diamond in the antecedent.
+        assert_eq!(correspondent("◇□P → □◇P"), None);
+
+        // More nested modalities than there are letters for the variables they bind.
+        let boxes = (0..30).fold(Proposition::variable('P'), |prop, _| Proposition::necessarily(prop));
+        let condition = frame_correspondent(&Proposition::if_then(parse_formula("□P").unwrap(), boxes)).unwrap().to_string();
+        assert!(condition.starts_with("∀x ∀y (R(x, y) → ∀z (R(y, z) → ∀u"), "{}", condition);
+        assert!(condition.contains("∀x30 (R(x29, x30) → "), "{}", condition);
+    }
+}
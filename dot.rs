@@ -0,0 +1,287 @@
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::error::{Error, Result};
+use crate::{Model, World};
+
+impl Model {
+    /// A Graphviz `digraph` with a node per world, labelled `"<world>: <atoms>"` (just `"<world>"` when no
+    /// atom holds there), an `xlabel` listing the nominals naming it, and an edge per accessibility pair.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph model {\n");
+        for world in self.worlds() {
+            let atoms: Vec<String> = self.atoms().into_iter().filter(|atom| self.holds(world, *atom)).map(|atom| atom.to_string()).collect();
+            let names: Vec<String> = self.names().filter(|(_, named)| *named == world).map(|(name, _)| name.to_string()).collect();
+            write!(dot, "  {} [label=\"{}", world, world).unwrap();
+            if !atoms.is_empty() {
+                write!(dot, ": {}", atoms.join(", ")).unwrap();
+            }
+            dot.push('"');
+            if !names.is_empty() {
+                write!(dot, ", xlabel=\"{}\"", names.join(", ")).unwrap();
+            }
+            dot.push_str("];\n");
+        }
+        for world in self.worlds() {
+            for next in self.successors(world) {
+                writeln!(dot, "  {} -> {};", world, next).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Reads a model from a Graphviz `digraph` whose node IDs are world numbers, such as `to_dot` writes.
+    ///
+    /// The atoms true at a world are the letters of its `label` after the first `:`, or of the whole
+    /// label if it has none, so both `"0: P, Q"` and a hand-written `"PQ"` work. The letters of an
+    /// `xlabel` are nominals naming the world. Edges may be chained, as in `0 -> 1 -> 2`.
+    /// Other attributes, and graph, `node` and `edge` defaults, are ignored.
+    pub fn from_dot(source: &str) -> Result<Model> {
+        let mut reader = DotReader { tokens: tokenize(source)?.into_iter().peekable(), line: 1 };
+        reader.expect_id("digraph")?;
+        if matches!(reader.peek(), Some(Token::Id(_))) {
+            reader.next();
+        }
+        reader.expect(Token::LeftBrace)?;
+        let mut model = Model::new();
+        loop {
+            match reader.next() {
+                Some(Token::RightBrace) => break,
+                Some(Token::Semicolon) => continue,
+                Some(Token::Id(id)) => reader.statement(&mut model, id)?,
+                Some(_) => return dot_error(reader.line, "expected a statement"),
+                None => return dot_error(reader.line, "expected `}` to close the graph"),
+            }
+        }
+        if reader.next().is_some() {
+            return dot_error(reader.line, "unexpected text after the graph");
+        }
+        Ok(model)
+    }
+}
+
+fn dot_error<T>(line: usize, message: &str) -> Result<T> {
+    Err(Error::Dot { line, message: message.to_string() })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    // Bare or quoted identifiers alike.
+    Id(String),
+    Arrow,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Equals,
+    Comma,
+    Semicolon,
+}
+
+// Each token is tagged with its (1-based) line. `//` and `#` comments run to the end of the line.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars: Peekable<CharIndices> = source.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '#' => {
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if(|(_, c)| *c == '/').is_some() => {
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                continue;
+            }
+            '-' if chars.next_if(|(_, c)| *c == '>').is_some() => Token::Arrow,
+            '{' => Token::LeftBrace,
+            '}' => Token::RightBrace,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '=' => Token::Equals,
+            ',' => Token::Comma,
+            ';' => Token::Semicolon,
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => text.push(c),
+                            None => return dot_error(line, "unterminated string"),
+                        },
+                        Some((_, c)) => {
+                            line += (c == '\n') as usize;
+                            text.push(c);
+                        }
+                        None => return dot_error(line, "unterminated string"),
+                    }
+                }
+                Token::Id(text)
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut end = index + c.len_utf8();
+                while let Some((next, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_' || *c == '.') {
+                    end = next + c.len_utf8();
+                }
+                Token::Id(source[index..end].to_string())
+            }
+            c => return dot_error(line, &format!("unexpected character `{}`", c)),
+        };
+        tokens.push((line, token));
+    }
+    Ok(tokens)
+}
+
+struct DotReader {
+    tokens: Peekable<std::vec::IntoIter<(usize, Token)>>,
+    // The line of the last token read.
+    line: usize,
+}
+
+impl DotReader {
+    fn next(&mut self) -> Option<Token> {
+        let (line, token) = self.tokens.next()?;
+        self.line = line;
+        Some(token)
+    }
+
+    fn peek(&mut self) -> Option<&Token> {
+        self.tokens.peek().map(|(_, token)| token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => dot_error(self.line, &format!("expected {:?}", expected)),
+        }
+    }
+
+    fn expect_id(&mut self, expected: &str) -> Result<()> {
+        match self.next() {
+            Some(Token::Id(id)) if id == expected => Ok(()),
+            _ => dot_error(self.line, &format!("expected `{}`", expected)),
+        }
+    }
+
+    // Reads the rest of a statement starting with `id`: a node, a chain of edges, a default or an attribute.
+    fn statement(&mut self, model: &mut Model, id: String) -> Result<()> {
+        if matches!(id.as_str(), "graph" | "node" | "edge") {
+            return self.attributes().map(|_| ());
+        }
+        if self.peek() == Some(&Token::Equals) {
+            self.next();
+            return match self.next() {
+                Some(Token::Id(_)) => Ok(()),
+                _ => dot_error(self.line, "expected a value"),
+            };
+        }
+        let mut world = self.world(&id)?;
+        if self.peek() != Some(&Token::Arrow) {
+            let mut atoms = Vec::new();
+            for (key, value) in self.attributes()? {
+                match key.as_str() {
+                    "label" => atoms.extend(value.split_once(':').map_or(value.as_str(), |(_, atoms)| atoms).chars().filter(|c| c.is_alphabetic())),
+                    "xlabel" => {
+                        for name in value.chars().filter(|c| c.is_alphabetic()) {
+                            model.name_world(name, world);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            model.add_world(world, &atoms);
+            return Ok(());
+        }
+        while self.peek() == Some(&Token::Arrow) {
+            self.next();
+            let next = match self.next() {
+                Some(Token::Id(id)) => self.world(&id)?,
+                _ => return dot_error(self.line, "expected a world after `->`"),
+            };
+            model.add_edge(world, next);
+            world = next;
+        }
+        self.attributes().map(|_| ())
+    }
+
+    fn world(&self, id: &str) -> Result<World> {
+        match id.parse() {
+            Ok(world) => Ok(world),
+            Err(_) => dot_error(self.line, &format!("expected a world number, found `{}`", id)),
+        }
+    }
+
+    // An optional `[key=value, ...]` list, which may be repeated.
+    fn attributes(&mut self) -> Result<Vec<(String, String)>> {
+        let mut attributes = Vec::new();
+        while self.peek() == Some(&Token::LeftBracket) {
+            self.next();
+            loop {
+                match self.next() {
+                    Some(Token::RightBracket) => break,
+                    Some(Token::Comma | Token::Semicolon) => continue,
+                    Some(Token::Id(key)) => {
+                        self.expect(Token::Equals)?;
+                        match self.next() {
+                            Some(Token::Id(value)) => attributes.push((key, value)),
+                            _ => return dot_error(self.line, "expected an attribute value"),
+                        }
+                    }
+                    _ => return dot_error(self.line, "expected an attribute or `]`"),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{evaluate_at, parse_enriched_formula};
+
+    #[test]
+    fn round_trips_through_dot() {
+        let mut model = Model::new();
+        model.add_world(0, &['P']);
+        model.add_world(1, &['P', 'Q']);
+        model.add_world(2, &['p']);
+        model.add_world(3, &[]);
+        model.add_edge(0, 1);
+        model.add_edge(1, 1);
+        model.add_edge(1, 3);
+        model.name_world('i', 3);
+        model.name_world('J', 2);
+        let dot = model.to_dot();
+        assert!(dot.contains("  1 [label=\"1: P, Q\"];\n"));
+        assert!(dot.contains("  2 [label=\"2: p\", xlabel=\"J\"];\n"));
+        assert!(dot.contains("  3 [label=\"3\", xlabel=\"i\"];\n"));
+        let read = Model::from_dot(&dot).unwrap();
+        assert_eq!(read, model);
+        for source in ["□P", "◇(Q ∧ ◇¬P)", "@i ¬Q", "E(P ∧ Q)"] {
+            let prop = parse_enriched_formula(source).unwrap();
+            for world in model.worlds() {
+                assert_eq!(evaluate_at(&read, world, &prop), evaluate_at(&model, world, &prop));
+            }
+        }
+    }
+
+    #[test]
+    fn reads_hand_written_dot() {
+        let source = "digraph {\n  rankdir=LR; node [shape=circle]\n  0 [label=\"PQ\"] // the root\n  0 -> 1 -> 2 [color=red]\n  2 -> 0\n}\n";
+        let model = Model::from_dot(source).unwrap();
+        assert_eq!(model.worlds().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(model.holds(0, 'Q'));
+        assert_eq!(model.successors(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(model.successors(2).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(Model::from_dot("digraph {\n  a -> 1\n}"), Err(Error::Dot { line: 2, message: "expected a world number, found `a`".to_string() }));
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeSet;
+
+use crate::{evaluate_at, Model, Proposition, World};
+
+/// Whether the agent at `world` knows `facts` and nothing more, in Levesque's sense: the worlds it
+/// considers possible are exactly the worlds where all of `facts` hold. Knowing more would rule out some
+/// of those worlds, and knowing less would leave some other world possible. The accessibility relation
+/// is read as the agent's, and should be an equivalence (S5) for the usual reading.
+pub fn only_knows(model: &Model, world: World, facts: &[Proposition]) -> bool {
+    let possible: BTreeSet<World> = model.successors(world).collect();
+    let fact_worlds: BTreeSet<World> = model.worlds().filter(|other| facts.iter().all(|fact| evaluate_at(model, *other, fact))).collect();
+    possible == fact_worlds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The S5 model on worlds 0 {P, Q}, 1 {P} and 2 {} whose indistinguishable worlds are each of `clusters`.
+    fn epistemic_model(clusters: &[&[World]]) -> Model {
+        let mut model = Model::new();
+        model.add_world(0, &['P', 'Q']);
+        model.add_world(1, &['P']);
+        model.add_world(2, &[]);
+        for cluster in clusters {
+            for from in *cluster {
+                for to in *cluster {
+                    model.add_edge(*from, *to);
+                }
+            }
+        }
+        model
+    }
+
+    #[test]
+    fn only_knowing_is_stronger_than_knowing() {
+        let p = Proposition::variable('P');
+        let knows_p = Proposition::necessarily(p.clone());
+
+        let unsure_of_q = epistemic_model(&[&[0, 1], &[2]]);
+        assert!(evaluate_at(&unsure_of_q, 0, &knows_p));
+        assert!(only_knows(&unsure_of_q, 0, std::slice::from_ref(&p)));
+
+        let sure_of_q = epistemic_model(&[&[0], &[1], &[2]]);
+        assert!(evaluate_at(&sure_of_q, 0, &knows_p));
+        assert!(!only_knows(&sure_of_q, 0, std::slice::from_ref(&p)));
+        assert!(only_knows(&sure_of_q, 0, &[p, Proposition::variable('Q')]));
+        assert!(only_knows(&sure_of_q, 2, &[Proposition::negation(Proposition::variable('P'))]));
+    }
+}
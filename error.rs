@@ -0,0 +1,125 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the source where parsing failed.
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(position: usize, message: &str) -> ParseError {
+        ParseError { position, message: message.to_string() }
+    }
+
+    /// Renders the line of `source` the error is on, with a caret under the offending character and the
+    /// message beside it:
+    ///
+    /// ```text
+    /// 1 | P ∧ ∨ Q
+    ///   |     ^ expected a proposition before the connective
+    /// ```
+    ///
+    /// A position inside a character points at that character.
+    pub fn render(&self, source: &str) -> String {
+        let position = (0..=self.position.min(source.len())).rev().find(|index| source.is_char_boundary(*index)).unwrap_or(0);
+        let line_start = source[..position].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[position..].find('\n').map_or(source.len(), |index| position + index);
+        let line_number = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..position].chars().count();
+        let gutter = " ".repeat(line_number.to_string().len());
+        format!(
+            "{} | {}\n{} | {}^ {}",
+            line_number,
+            &source[line_start..line_end],
+            gutter,
+            " ".repeat(column),
+            self.message
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    /// The token at `position` needed more operands than the stack held.
+    StackUnderflow { position: usize },
+    /// The token stream left `count` values on the stack instead of exactly one.
+    LeftoverOperands { count: usize },
+    EmptyExpression,
+    /// The atom has no value in the assignment it was evaluated under.
+    UnassignedAtom(char),
+    /// A fixture file was malformed at the given (1-based) line.
+    Fixture { line: usize, message: String },
+    /// A DOT description was malformed at the given (1-based) line.
+    Dot { line: usize, message: String },
+    /// A Horn-only algorithm was given a formula outside the Horn fragment.
+    NotHorn,
+    /// A procedure for the basic modal language was given a formula with nominals, the global
+    /// operators, `Y` or `C_G`.
+    NotBasicModal,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(error) => error.fmt(f),
+            Error::StackUnderflow { position } => write!(f, "token {} is missing an operand", position),
+            Error::LeftoverOperands { count } => write!(f, "expected a single result but {} values were left over", count),
+            Error::EmptyExpression => write!(f, "empty expression"),
+            Error::UnassignedAtom(name) => write!(f, "atom {} has no assigned value", name),
+            Error::Fixture { line, message } => write!(f, "{} on line {}", message, line),
+            Error::Dot { line, message } => write!(f, "{} on line {}", message, line),
+            Error::NotHorn => write!(f, "formula is not a Horn formula"),
+            Error::NotBasicModal => write!(f, "formula is not in the basic modal language"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Error {
+        Error::Parse(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    fn parse_error_for(source: &str) -> ParseError {
+        match parse_formula(source) {
+            Err(Error::Parse(error)) => error,
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn renders_a_caret_under_the_bad_token() {
+        let source = "P ∧ ∨ Q";
+        assert_eq!(parse_error_for(source).render(source), "1 | P ∧ ∨ Q\n  |     ^ expected a proposition before the connective");
+        let source = "(P → Q";
+        assert_eq!(parse_error_for(source).render(source), "1 | (P → Q\n  | ^ unclosed parenthesis");
+    }
+
+    #[test]
+    fn renders_errors_at_the_end_of_the_source() {
+        let source = "P → ";
+        assert_eq!(parse_error_for(source).render(source), "1 | P → \n  |     ^ expected a proposition");
+    }
+
+    #[test]
+    fn renders_positions_inside_a_character() {
+        assert_eq!(ParseError::new(1, "expected an atom").render("¬P"), "1 | ¬P\n  | ^ expected an atom");
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::error::{Error, Result};
+use crate::{eval_rpn, evaluate_at, parse_formula, to_rpn, try_parse_proposition_string, Model, World};
+
+/// A named formula and the truth value it's expected to have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub name: String,
+    pub formula: String,
+    /// The model and world to evaluate at. Without one, `formula` carries its own `;` assignments.
+    pub at: Option<(String, World)>,
+    pub expected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub name: String,
+    pub expected: bool,
+    pub actual: bool,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// Models and cases read from a TOML file of `[[model]]` and `[[case]]` tables:
+///
+/// ```toml
+/// [[model]]
+/// name = "chain"
+/// worlds = { 0 = "P", 1 = "PQ", 2 = "" }
+/// edges = [[0, 1], [1, 2]]
+///
+/// [[case]]
+/// name = "Q is possible"
+/// formula = "◇Q"
+/// model = "chain"
+/// world = 0
+/// expected = true
+/// ```
+///
+/// Only the subset of TOML these files need is understood, and each value must fit on one line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fixture {
+    pub models: BTreeMap<String, Model>,
+    pub cases: Vec<Case>,
+}
+
+impl Fixture {
+    pub fn from_toml(source: &str) -> Result<Fixture> {
+        let mut fixture = Fixture::default();
+        let mut case_lines = Vec::new();
+        for (kind, line, table) in parse_tables(source)? {
+            match kind.as_str() {
+                "model" => {
+                    let (name, model) = model_from_table(&table, line)?;
+                    if fixture.models.insert(name, model).is_some() {
+                        return fixture_error(line, "duplicate model name");
+                    }
+                }
+                "case" => {
+                    fixture.cases.push(case_from_table(&table, line)?);
+                    case_lines.push(line);
+                }
+                _ => return fixture_error(line, &format!("unknown table `[[{}]]`", kind)),
+            }
+        }
+        for (case, line) in fixture.cases.iter().zip(case_lines) {
+            if let Some((model, world)) = &case.at {
+                match fixture.models.get(model) {
+                    None => return fixture_error(line, &format!("unknown model `{}`", model)),
+                    Some(found) if !found.worlds().any(|other| other == *world) => {
+                        return fixture_error(line, &format!("model `{}` has no world {}", model, world))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(fixture)
+    }
+
+    /// Evaluates every case, failing only if a formula can't be parsed or evaluated.
+    pub fn run(&self) -> Result<Vec<Outcome>> {
+        self.cases
+            .iter()
+            .map(|case| {
+                let actual = match &case.at {
+                    Some((model, world)) => evaluate_at(&self.models[model], *world, &parse_formula(&case.formula)?),
+                    None => eval_rpn(&to_rpn(&try_parse_proposition_string(&case.formula)?), &HashMap::new())?,
+                };
+                Ok(Outcome { name: case.name.clone(), expected: case.expected, actual })
+            })
+            .collect()
+    }
+}
+
+fn model_from_table(table: &Table, line: usize) -> Result<(String, Model)> {
+    let mut model = Model::new();
+    let worlds = match table.get("worlds") {
+        Some(Value::Table(worlds)) => worlds,
+        _ => return fixture_error(line, "expected `worlds` to be a table of world = \"atoms\""),
+    };
+    for (world, atoms) in worlds {
+        let world = match world.parse::<World>() {
+            Ok(world) => world,
+            Err(_) => return fixture_error(line, &format!("invalid world `{}`", world)),
+        };
+        let atoms: Vec<char> = match atoms {
+            Value::String(atoms) => atoms.chars().filter(|c| !c.is_whitespace()).collect(),
+            _ => return fixture_error(line, "expected the atoms true at a world as a string"),
+        };
+        model.add_world(world, &atoms);
+    }
+    match table.get("edges") {
+        None => (),
+        Some(Value::Array(edges)) => {
+            for edge in edges {
+                match edge {
+                    Value::Array(pair) => match pair.as_slice() {
+                        [Value::Integer(from), Value::Integer(to)] if *from >= 0 && *to >= 0 => model.add_edge(*from as World, *to as World),
+                        _ => return fixture_error(line, "expected an edge as [from, to]"),
+                    },
+                    _ => return fixture_error(line, "expected an edge as [from, to]"),
+                }
+            }
+        }
+        Some(_) => return fixture_error(line, "expected `edges` to be an array"),
+    }
+    Ok((string_value(table, "name", line)?.to_string(), model))
+}
+
+fn case_from_table(table: &Table, line: usize) -> Result<Case> {
+    let at = match table.get("model") {
+        None => None,
+        Some(_) => {
+            let world = match table.get("world") {
+                Some(Value::Integer(world)) if *world >= 0 => *world as World,
+                _ => return fixture_error(line, "expected `world` alongside `model`"),
+            };
+            Some((string_value(table, "model", line)?.to_string(), world))
+        }
+    };
+    let expected = match table.get("expected") {
+        Some(Value::Boolean(expected)) => *expected,
+        _ => return fixture_error(line, "expected `expected` to be true or false"),
+    };
+    Ok(Case {
+        name: string_value(table, "name", line)?.to_string(),
+        formula: string_value(table, "formula", line)?.to_string(),
+        at,
+        expected,
+    })
+}
+
+fn string_value<'a>(table: &'a Table, key: &str, line: usize) -> Result<&'a str> {
+    match table.get(key) {
+        Some(Value::String(value)) => Ok(value),
+        _ => fixture_error(line, &format!("expected `{}` to be a string", key)),
+    }
+}
+
+fn fixture_error<T>(line: usize, message: &str) -> Result<T> {
+    Err(Error::Fixture { line, message: message.to_string() })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+type Table = BTreeMap<String, Value>;
+
+// Splits the source into its `[[kind]]` tables, each tagged with the line of its header.
+fn parse_tables(source: &str) -> Result<Vec<(String, usize, Table)>> {
+    let mut tables: Vec<(String, usize, Table)> = Vec::new();
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = strip_comment(text).trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(kind) = text.strip_prefix("[[").and_then(|text| text.strip_suffix("]]")) {
+            tables.push((kind.trim().to_string(), line, Table::new()));
+            continue;
+        }
+        let (key, value) = match text.split_once('=') {
+            Some((key, value)) if is_bare_key(key.trim()) => (key.trim(), value),
+            _ => return fixture_error(line, "expected `key = value`"),
+        };
+        let table = match tables.last_mut() {
+            Some((_, _, table)) => table,
+            None => return fixture_error(line, "expected a `[[table]]` header before any keys"),
+        };
+        let mut reader = ValueReader { chars: value.chars().peekable(), line };
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        if reader.chars.next().is_some() {
+            return fixture_error(line, "unexpected text after value");
+        }
+        if table.insert(key.to_string(), value).is_some() {
+            return fixture_error(line, &format!("duplicate key `{}`", key));
+        }
+    }
+    Ok(tables)
+}
+
+fn strip_comment(text: &str) -> &str {
+    let mut in_string = false;
+    let mut chars = text.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            '#' if !in_string => return &text[..index],
+            _ => (),
+        }
+    }
+    text
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+struct ValueReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl ValueReader<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(c) if c.is_ascii_digit() || *c == '-' || *c == '+' => {
+                let word = self.word();
+                match word.parse() {
+                    Ok(integer) => Ok(Value::Integer(integer)),
+                    Err(_) => fixture_error(self.line, &format!("invalid integer `{}`", word)),
+                }
+            }
+            Some(_) => match self.word().as_str() {
+                "true" => Ok(Value::Boolean(true)),
+                "false" => Ok(Value::Boolean(false)),
+                word => fixture_error(self.line, &format!("unsupported value `{}`", word)),
+            },
+            None => fixture_error(self.line, "missing value"),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-' || *c == '+') {
+            word.push(c);
+        }
+        word
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.chars.next();
+        let mut string = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(string),
+                '\\' => match self.chars.next() {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    _ => return fixture_error(self.line, "unsupported escape in string"),
+                },
+                c => string.push(c),
+            }
+        }
+        fixture_error(self.line, "unterminated string")
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.chars.next();
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.chars.next_if_eq(&']').is_some() {
+                return Ok(Value::Array(values));
+            }
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return fixture_error(self.line, "expected `,` or `]` in array"),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value> {
+        self.chars.next();
+        let mut table = Table::new();
+        loop {
+            self.skip_whitespace();
+            if self.chars.next_if_eq(&'}').is_some() {
+                return Ok(Value::Table(table));
+            }
+            let key = self.word();
+            self.skip_whitespace();
+            if key.is_empty() || self.chars.next() != Some('=') {
+                return fixture_error(self.line, "expected `key = value` in inline table");
+            }
+            let value = self.value()?;
+            if table.insert(key.clone(), value).is_some() {
+                return fixture_error(self.line, &format!("duplicate key `{}`", key));
+            }
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some('}') => return Ok(Value::Table(table)),
+                _ => return fixture_error(self.line, "expected `,` or `}` in inline table"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_in_repo_fixture() {
+        let fixture = Fixture::from_toml(include_str!("fixtures/basic.toml")).unwrap();
+        assert_eq!(fixture.models.len(), 2);
+        let outcomes = fixture.run().unwrap();
+        assert_eq!(outcomes.len(), fixture.cases.len());
+        for outcome in outcomes {
+            assert!(outcome.passed(), "fixture case `{}` evaluated to {}", outcome.name, outcome.actual);
+        }
+    }
+
+    #[test]
+    fn reports_malformed_fixtures() {
+        let unknown_model = "[[case]]\nname = \"x\"\nformula = \"◇P\"\nmodel = \"missing\"\nworld = 0\nexpected = true\n";
+        assert_eq!(
+            Fixture::from_toml(unknown_model),
+            Err(Error::Fixture { line: 1, message: "unknown model `missing`".to_string() })
+        );
+        let unknown_world = "[[model]]\nname = \"m\"\nworlds = { 0 = \"P\" }\n\n[[case]]\nname = \"x\"\nformula = \"□P\"\nmodel = \"m\"\nworld = 9\nexpected = true\n";
+        assert_eq!(
+            Fixture::from_toml(unknown_world),
+            Err(Error::Fixture { line: 5, message: "model `m` has no world 9".to_string() })
+        );
+        let bad_value = "[[case]]\nname = \"x\"\nexpected = maybe\n";
+        assert_eq!(
+            Fixture::from_toml(bad_value),
+            Err(Error::Fixture { line: 3, message: "unsupported value `maybe`".to_string() })
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{to_cnf, Atom, Connective, Proposition};
+
+/// Tractable classes of propositional formulas, after Schaefer's dichotomy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fragment {
+    /// Every clause of the CNF has at most one positive literal.
+    Horn,
+    /// Every clause of the CNF has at most two literals.
+    TwoCnf,
+    /// No literal of the CNF is negative.
+    Monotone,
+    /// A conjunction of XOR equations: each conjunct is built from atoms and constants with `¬` and `↔` only.
+    Affine,
+    General,
+}
+
+/// The first of `Horn`, `TwoCnf`, `Monotone` and `Affine` that `prop` belongs to, or `General` when none
+/// fits or `prop` has modal operators. A formula can lie in several fragments (`P ∧ Q` is in all four), and
+/// this order puts first the ones with the most widely implemented solvers.
+pub fn fragment(prop: &Proposition) -> Fragment {
+    let clauses = match to_cnf(prop) {
+        Some(clauses) => clauses,
+        None => return Fragment::General,
+    };
+    if clauses.iter().all(|clause| clause.iter().filter(|literal| literal.positive).count() <= 1) {
+        Fragment::Horn
+    } else if clauses.iter().all(|clause| clause.len() <= 2) {
+        Fragment::TwoCnf
+    } else if clauses.iter().flatten().all(|literal| literal.positive) {
+        Fragment::Monotone
+    } else if is_affine(prop) {
+        Fragment::Affine
+    } else {
+        Fragment::General
+    }
+}
+
+fn is_affine(prop: &Proposition) -> bool {
+    match prop {
+        Proposition::Connective(Connective::And(left, right)) => is_affine(left) && is_affine(right),
+        Proposition::Parenthesised(prop) => is_affine(prop),
+        _ => is_linear(prop),
+    }
+}
+
+fn is_linear(prop: &Proposition) -> bool {
+    match prop {
+        Proposition::Atom(Atom::Variable(_) | Atom::True | Atom::False) => true,
+        Proposition::Connective(Connective::Not(prop)) | Proposition::Parenthesised(prop) => is_linear(prop),
+        Proposition::Connective(Connective::Iff(left, right)) => is_linear(left) && is_linear(right),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    fn classify(source: &str) -> Fragment {
+        fragment(&parse_formula(source).unwrap())
+    }
+
+    #[test]
+    fn classifies_horn_formulas() {
+        assert_eq!(classify("(P ∧ Q → R) ∧ (R → S) ∧ ¬(S ∧ P)"), Fragment::Horn);
+        assert_eq!(classify("P ∧ ¬Q"), Fragment::Horn);
+    }
+
+    #[test]
+    fn classifies_the_other_fragments() {
+        assert_eq!(classify("(P ∨ Q) ∧ (¬P ∨ R) ∧ (Q ∨ R)"), Fragment::TwoCnf);
+        assert_eq!(classify("(P ∨ Q ∨ R) ∧ (Q ∨ S ∨ P)"), Fragment::Monotone);
+        assert_eq!(classify("(¬(P ↔ (Q ↔ R))) ∧ (R ↔ S)"), Fragment::Affine);
+        assert_eq!(classify("(P ∨ Q ∨ ¬R) ∧ (¬P ∨ S ∨ R)"), Fragment::General);
+        assert_eq!(classify("□P ∧ Q"), Fragment::General);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{Atom, Proof, Proposition};
+
+/// A Hilbert-style axiom system supplied by the user: axiom schemas, closed under modus ponens and,
+/// optionally, necessitation. In a schema every atom is a placeholder for any formula, so `P → (Q → P)`
+/// stands for all its instances; nominals and constants stand for themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AxiomSystem {
+    axioms: Vec<Proposition>,
+    necessitation: bool,
+}
+
+impl AxiomSystem {
+    pub fn new() -> AxiomSystem {
+        AxiomSystem::default()
+    }
+
+    pub fn add_axiom(&mut self, schema: Proposition) {
+        self.axioms.push(schema);
+    }
+
+    pub fn axioms(&self) -> &[Proposition] {
+        &self.axioms
+    }
+
+    /// Adds the rule of necessitation, from `φ` to `□φ`, as normal modal logics have.
+    pub fn enable_necessitation(&mut self) {
+        self.necessitation = true;
+    }
+
+    /// A derivation of `target`, if there is one nesting rule applications no more than `max_depth`
+    /// deep, and the shallowest such. Axiom steps are justified `axiom n` for the `n`th axiom added
+    /// (1-based); unconstrained parts of the instances they need are filled in with `⊤`. The search is
+    /// exhaustive up to the bound, so `None` means no derivation within it.
+    pub fn derive(&self, target: &Proposition, max_depth: usize) -> Option<Proof> {
+        let goal = Term::fixed(target);
+        (0..=max_depth).find_map(|depth| {
+            self.prove(&goal, depth, Vec::new(), &mut |bindings, derivation| {
+                let mut proof = Proof::new();
+                derivation.write(&bindings, &mut proof);
+                Some(proof)
+            })
+        })
+    }
+
+    // Backward search for derivations of `goal` under `bindings`, handing each one found to `found`
+    // until it accepts one.
+    fn prove(&self, goal: &Term, depth: usize, bindings: Bindings, found: &mut dyn FnMut(Bindings, Derivation) -> Option<Proof>) -> Option<Proof> {
+        for (index, schema) in self.axioms.iter().enumerate() {
+            let mut bindings = bindings.clone();
+            let instance = Term::schema(schema, &mut bindings);
+            if unify(goal, &instance, &mut bindings) {
+                if let Some(proof) = found(bindings, Derivation::Axiom(index, goal.clone())) {
+                    return Some(proof);
+                }
+            }
+        }
+        if depth == 0 {
+            return None;
+        }
+        if self.necessitation {
+            let mut bindings = bindings.clone();
+            let operand = Term::Meta(bindings.len());
+            bindings.push(None);
+            if unify(goal, &Term::Node(Proposition::necessarily(Proposition::constant(true)), vec![operand.clone()]), &mut bindings) {
+                let proof = self.prove(&operand, depth - 1, bindings, &mut |bindings, premise| {
+                    found(bindings, Derivation::Necessitation(Box::new(premise), goal.clone()))
+                });
+                if proof.is_some() {
+                    return proof;
+                }
+            }
+        }
+        // Modus ponens, from some `φ → goal` and `φ`, with φ left for the implication's proof to settle.
+        let mut bindings = bindings;
+        let antecedent = Term::Meta(bindings.len());
+        bindings.push(None);
+        let implication = Term::Node(Proposition::if_then(Proposition::constant(true), Proposition::constant(true)), vec![antecedent.clone(), goal.clone()]);
+        self.prove(&implication, depth - 1, bindings, &mut |bindings, major| {
+            self.prove(&antecedent, depth - 1, bindings, &mut |bindings, minor| {
+                found(bindings, Derivation::ModusPonens(Box::new(major.clone()), Box::new(minor), goal.clone()))
+            })
+        })
+    }
+}
+
+// What each placeholder introduced so far stands for, if it's been settled.
+type Bindings = Vec<Option<Term>>;
+
+// A formula with placeholders. A node keeps its connective (or atom) as a proposition whose operands
+// are all `⊤`, so two nodes agree on it exactly when those are equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Meta(usize),
+    Node(Proposition, Vec<Term>),
+}
+
+impl Term {
+    fn fixed(prop: &Proposition) -> Term {
+        Term::build(prop, &mut |_| None)
+    }
+
+    // An instance of `schema` with a fresh placeholder for each of its atoms.
+    fn schema(schema: &Proposition, bindings: &mut Bindings) -> Term {
+        let mut metas: Vec<(char, usize)> = Vec::new();
+        Term::build(schema, &mut |atom| {
+            if let Some((_, meta)) = metas.iter().find(|(seen, _)| *seen == atom) {
+                return Some(*meta);
+            }
+            bindings.push(None);
+            metas.push((atom, bindings.len() - 1));
+            Some(bindings.len() - 1)
+        })
+    }
+
+    fn build(prop: &Proposition, meta: &mut impl FnMut(char) -> Option<usize>) -> Term {
+        match prop {
+            Proposition::Parenthesised(prop) => Term::build(prop, meta),
+            Proposition::Atom(Atom::Variable(atom)) => match meta(*atom) {
+                Some(index) => Term::Meta(index),
+                None => Term::Node(prop.clone(), vec![]),
+            },
+            _ => {
+                let operands = prop.operands();
+                let shape = prop.with_operands(vec![Proposition::constant(true); operands.len()]);
+                Term::Node(shape, operands.into_iter().map(|operand| Term::build(operand, meta)).collect())
+            }
+        }
+    }
+
+    // The formula this stands for under `bindings`, with `⊤` for any placeholder left open.
+    fn resolve(&self, bindings: &Bindings) -> Proposition {
+        match self {
+            Term::Meta(index) => bindings[*index].as_ref().map_or(Proposition::constant(true), |term| term.resolve(bindings)),
+            Term::Node(shape, operands) => shape.with_operands(operands.iter().map(|operand| operand.resolve(bindings)).collect()),
+        }
+    }
+}
+
+// Follows `term`'s bindings until it's a node or an open placeholder.
+fn walk<'a>(term: &'a Term, bindings: &'a Bindings) -> &'a Term {
+    match term {
+        Term::Meta(index) => bindings[*index].as_ref().map_or(term, |bound| walk(bound, bindings)),
+        Term::Node(..) => term,
+    }
+}
+
+fn occurs(meta: usize, term: &Term, bindings: &Bindings) -> bool {
+    match walk(term, bindings) {
+        Term::Meta(index) => *index == meta,
+        Term::Node(_, operands) => operands.iter().any(|operand| occurs(meta, operand, bindings)),
+    }
+}
+
+// Extends `bindings` so `left` and `right` stand for the same formula, if they can. On failure
+// `bindings` may be left half-extended.
+fn unify(left: &Term, right: &Term, bindings: &mut Bindings) -> bool {
+    match (walk(left, bindings).clone(), walk(right, bindings).clone()) {
+        (Term::Meta(left), Term::Meta(right)) if left == right => true,
+        (Term::Meta(meta), term) | (term, Term::Meta(meta)) => {
+            if occurs(meta, &term, bindings) {
+                return false;
+            }
+            bindings[meta] = Some(term);
+            true
+        }
+        (Term::Node(left_shape, left), Term::Node(right_shape, right)) => {
+            left_shape == right_shape && left.iter().zip(&right).all(|(left, right)| unify(left, right, bindings))
+        }
+    }
+}
+
+// A derivation found by the search, each step with the (possibly still open) formula it derives.
+#[derive(Debug, Clone)]
+enum Derivation {
+    Axiom(usize, Term),
+    ModusPonens(Box<Derivation>, Box<Derivation>, Term),
+    Necessitation(Box<Derivation>, Term),
+}
+
+impl Derivation {
+    // Adds the steps of the derivation to `proof`, premises first, returning the index of its conclusion.
+    fn write(&self, bindings: &Bindings, proof: &mut Proof) -> usize {
+        match self {
+            Derivation::Axiom(index, formula) => proof.add(formula.resolve(bindings), &format!("axiom {}", index + 1), &[]),
+            Derivation::ModusPonens(major, minor, formula) => {
+                let major = major.write(bindings, proof);
+                let minor = minor.write(bindings, proof);
+                proof.add(formula.resolve(bindings), "MP", &[major, minor])
+            }
+            Derivation::Necessitation(premise, formula) => {
+                let premise = premise.write(bindings, proof);
+                proof.add(formula.resolve(bindings), "Nec", &[premise])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    // The implicational axioms of classical propositional logic.
+    fn implicational() -> AxiomSystem {
+        let mut system = AxiomSystem::new();
+        system.add_axiom(parse_formula("P → (Q → P)").unwrap());
+        system.add_axiom(parse_formula("(P → (Q → R)) → ((P → Q) → (P → R))").unwrap());
+        system
+    }
+
+    #[test]
+    fn derives_theorems_from_user_axioms() {
+        let system = implicational();
+        let identity = parse_formula("P → P").unwrap();
+        let proof = system.derive(&identity, 3).expect("P → P is derivable");
+        let steps = proof.steps();
+        assert_eq!(steps.last().unwrap().formula, identity);
+        assert_eq!(steps.iter().filter(|step| step.justification == "MP").count(), 2);
+        // Every step is an axiom instance or follows from the ones it cites.
+        for step in steps {
+            if step.justification == "MP" {
+                let implication = Proposition::if_then(steps[step.from[1]].formula.clone(), step.formula.clone());
+                assert_eq!(steps[step.from[0]].formula, implication);
+            }
+        }
+        assert!(system.derive(&identity, 1).is_none());
+        assert!(system.derive(&parse_formula("P → Q").unwrap(), 3).is_none());
+    }
+
+    #[test]
+    fn applies_necessitation_when_enabled() {
+        let mut system = implicational();
+        let boxed = parse_formula("□(Q → (P → Q))").unwrap();
+        assert!(system.derive(&boxed, 2).is_none());
+        system.enable_necessitation();
+        let proof = system.derive(&boxed, 2).unwrap();
+        assert_eq!(proof.to_text(), "1. Q → (P → Q)       axiom 1\n2. □(Q → (P → Q))    Nec 1\n");
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::Write;
+
+use crate::{Model, TruthTable};
+
+impl TruthTable {
+    /// A self-contained HTML document holding a plain `<table>`, with a column per atom followed by one
+    /// for the formula, and `T`/`F` cells.
+    pub fn to_html(&self) -> String {
+        let formula = escape_html(&self.formula().to_string());
+        let mut html = String::from("<table>\n  <tr>");
+        for atom in self.atoms() {
+            write!(html, "<th>{}</th>", escape_html(&atom.to_string())).unwrap();
+        }
+        writeln!(html, "<th>{}</th></tr>", formula).unwrap();
+        for row in self.rows() {
+            html.push_str("  <tr>");
+            for value in row.values.iter().chain([&row.result]) {
+                write!(html, "<td>{}</td>", if *value { 'T' } else { 'F' }).unwrap();
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        document(&formula, &html)
+    }
+}
+
+impl Model {
+    /// A self-contained HTML document holding a plain `<table>` with a row per world: its names, the
+    /// atoms true there, and the worlds it can access.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<table>\n  <tr><th>World</th><th>Names</th><th>True atoms</th><th>Accessible worlds</th></tr>\n");
+        for world in self.worlds() {
+            let names: Vec<String> = self.names().filter(|(_, named)| *named == world).map(|(name, _)| escape_html(&name.to_string())).collect();
+            let atoms: Vec<String> = self.atoms().into_iter().filter(|atom| self.holds(world, *atom)).map(|atom| escape_html(&atom.to_string())).collect();
+            let successors: Vec<String> = self.successors(world).map(|next| next.to_string()).collect();
+            writeln!(html, "  <tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", world, names.join(", "), atoms.join(", "), successors.join(", ")).unwrap();
+        }
+        html.push_str("</table>\n");
+        document("Model", &html)
+    }
+}
+
+// A minimal page around `body`, declaring the encoding so the connectives display as they are.
+fn document(title: &str, body: &str) -> String {
+    format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n", title, body)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_formula, Proposition};
+
+    #[test]
+    fn renders_truth_tables_as_html() {
+        let html = TruthTable::new(&parse_formula("P ∧ Q").unwrap()).to_html();
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>P ∧ Q</title>"));
+        assert!(html.contains("<body>\n<table>\n  <tr><th>P</th><th>Q</th><th>P ∧ Q</th></tr>\n"));
+        assert!(html.ends_with("</table>\n</body>\n</html>\n"));
+        assert_eq!(html.matches("<tr>").count(), 5);
+        assert!(html.contains("<tr><td>T</td><td>T</td><td>T</td></tr>"));
+        assert_eq!(html.matches("<td>").count(), 12);
+    }
+
+    #[test]
+    fn renders_models_as_html() {
+        let mut model = Model::new();
+        model.add_world(0, &['P', 'Q']);
+        model.add_edge(0, 1);
+        model.add_edge(0, 2);
+        model.name_world('i', 1);
+        let html = model.to_html();
+        assert!(html.contains("<tr><th>World</th><th>Names</th><th>True atoms</th><th>Accessible worlds</th></tr>"));
+        assert_eq!(html.matches("<tr>").count(), 4);
+        assert!(html.contains("<tr><td>0</td><td></td><td>P, Q</td><td>1, 2</td></tr>"));
+        assert!(html.contains("<tr><td>1</td><td>i</td><td></td><td></td></tr>"));
+        assert!(html.contains("<title>Model</title>") && html.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn escapes_every_cell() {
+        let mut model = Model::new();
+        model.add_world(0, &['<', '&']);
+        model.name_world('"', 0);
+        let html = model.to_html();
+        assert!(html.contains("<tr><td>0</td><td>&quot;</td><td>&amp;, &lt;</td><td></td></tr>"));
+        let prop = Proposition::and(Proposition::variable('<'), Proposition::variable('>'));
+        let html = TruthTable::new(&prop).to_html();
+        assert!(html.contains("<title>&lt; ∧ &gt;</title>"));
+        assert!(html.contains("<tr><th>&lt;</th><th>&gt;</th><th>&lt; ∧ &gt;</th></tr>"));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Atom, Connective, Group, Model, Proposition, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    True,
+    False,
+    Variable(char),
+    Nominal(char),
+    And(usize, usize),
+    Or(usize, usize),
+    IfThen(usize, usize),
+    Iff(usize, usize),
+    Not(usize),
+    Possibly(usize),
+    Necessarily(usize),
+    Everywhere(usize),
+    Somewhere(usize),
+    At(char, usize),
+    Elsewhere(usize),
+    Counterfactual(usize, usize),
+    Knows(char, usize),
+    Next(usize),
+    Previous(usize),
+    CommonKnowledge(Group, usize),
+}
+
+type Entry = (World, usize);
+
+/// A formula compiled against one `Model`, caching the value of every subformula at every world it's
+/// been asked about. Editing the model through `set_atom`, `add_edge` or `remove_edge` drops only the
+/// cached values that read what changed, directly or through other cached values.
+#[derive(Debug, Clone)]
+pub struct CompiledFormula {
+    model: Model,
+    // Subformulas in post-order, so the whole formula is the last node.
+    nodes: Vec<Node>,
+    cache: HashMap<Entry, bool>,
+    // For each cached entry, the entries whose values were computed from it.
+    dependents: HashMap<Entry, HashSet<Entry>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl CompiledFormula {
+    pub fn new(model: Model, prop: &Proposition) -> CompiledFormula {
+        let mut nodes = Vec::new();
+        compile(prop, &mut nodes);
+        CompiledFormula { model, nodes, cache: HashMap::new(), dependents: HashMap::new(), hits: 0, misses: 0 }
+    }
+
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    pub fn evaluate(&mut self, world: World) -> bool {
+        self.value(world, self.nodes.len() - 1)
+    }
+
+    /// Number of subformula values answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of subformula values that had to be computed.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn set_atom(&mut self, world: World, atom: char, value: bool) {
+        if self.model.holds(world, atom) == value {
+            return;
+        }
+        let worlds = self.model.worlds().count();
+        self.model.set_atom(world, atom, value);
+        let stale: Vec<Entry> = (0..self.nodes.len()).filter(|node| self.nodes[*node] == Node::Variable(atom)).map(|node| (world, node)).collect();
+        self.invalidate(stale);
+        self.invalidate_global_nodes(worlds);
+    }
+
+    pub fn add_edge(&mut self, from: World, to: World) {
+        let worlds = self.model.worlds().count();
+        self.model.add_edge(from, to);
+        self.invalidate_modal_nodes(from, to);
+        self.invalidate_global_nodes(worlds);
+    }
+
+    pub fn remove_edge(&mut self, from: World, to: World) {
+        self.model.remove_edge(from, to);
+        self.invalidate_modal_nodes(from, to);
+    }
+
+    // An edge from `from` to `to` changes what `from` sees ahead of it, and what `to` sees behind it.
+    fn invalidate_modal_nodes(&mut self, from: World, to: World) {
+        let stale: Vec<Entry> = (0..self.nodes.len())
+            .filter_map(|node| match self.nodes[node] {
+                Node::Possibly(_) | Node::Necessarily(_) | Node::Counterfactual(..) | Node::Knows(..) | Node::Next(_) => Some((from, node)),
+                Node::Previous(_) => Some((to, node)),
+                _ => None,
+            })
+            .collect();
+        self.invalidate(stale);
+        // Common knowledge reads every world reachable from its own, and what's reachable from any world
+        // that reaches `from` may have changed.
+        let stale: Vec<Entry> = self.cache.keys().filter(|(_, node)| matches!(self.nodes[*node], Node::CommonKnowledge(..))).copied().collect();
+        self.invalidate(stale);
+    }
+
+    // A new world is one more world for `A`, `E` and `D` to range over, everywhere.
+    fn invalidate_global_nodes(&mut self, worlds_before: usize) {
+        if self.model.worlds().count() == worlds_before {
+            return;
+        }
+        let stale: Vec<Entry> = self
+            .cache
+            .keys()
+            .filter(|(_, node)| matches!(self.nodes[*node], Node::Everywhere(_) | Node::Somewhere(_) | Node::Elsewhere(_)))
+            .copied()
+            .collect();
+        self.invalidate(stale);
+    }
+
+    fn invalidate(&mut self, mut stale: Vec<Entry>) {
+        while let Some(entry) = stale.pop() {
+            if self.cache.remove(&entry).is_some() {
+                stale.extend(self.dependents.remove(&entry).into_iter().flatten());
+            }
+        }
+    }
+
+    fn value(&mut self, world: World, node: usize) -> bool {
+        if let Some(value) = self.cache.get(&(world, node)) {
+            self.hits += 1;
+            return *value;
+        }
+        self.misses += 1;
+        let entry = (world, node);
+        let value = match self.nodes[node] {
+            Node::True => true,
+            Node::False => false,
+            Node::Variable(name) => self.model.holds(world, name),
+            Node::Nominal(name) => self.model.named(name) == Some(world),
+            Node::And(left, right) => self.read(entry, world, left) && self.read(entry, world, right),
+            Node::Or(left, right) => self.read(entry, world, left) || self.read(entry, world, right),
+            Node::IfThen(left, right) => !self.read(entry, world, left) || self.read(entry, world, right),
+            Node::Iff(left, right) => self.read(entry, world, left) == self.read(entry, world, right),
+            Node::Not(prop) => !self.read(entry, world, prop),
+            Node::Possibly(prop) => {
+                let successors: Vec<World> = self.model.successors(world).collect();
+                successors.into_iter().any(|next| self.read(entry, next, prop))
+            }
+            Node::Necessarily(prop) | Node::Knows(_, prop) => {
+                let successors: Vec<World> = self.model.successors(world).collect();
+                successors.into_iter().all(|next| self.read(entry, next, prop))
+            }
+            Node::Everywhere(prop) => {
+                let worlds: Vec<World> = self.model.worlds().collect();
+                worlds.into_iter().all(|other| self.read(entry, other, prop))
+            }
+            Node::Somewhere(prop) => {
+                let worlds: Vec<World> = self.model.worlds().collect();
+                worlds.into_iter().any(|other| self.read(entry, other, prop))
+            }
+            Node::At(name, prop) => match self.model.named(name) {
+                Some(named) => self.read(entry, named, prop),
+                None => false,
+            },
+            Node::Elsewhere(prop) => {
+                let worlds: Vec<World> = self.model.worlds().filter(|other| *other != world).collect();
+                worlds.into_iter().any(|other| self.read(entry, other, prop))
+            }
+            Node::Next(prop) => {
+                let successors: Vec<World> = self.model.successors(world).collect();
+                !successors.is_empty() && successors.into_iter().all(|next| self.read(entry, next, prop))
+            }
+            Node::Previous(prop) => {
+                let predecessors: Vec<World> = self.model.worlds().filter(|other| self.model.successors(*other).any(|next| next == world)).collect();
+                !predecessors.is_empty() && predecessors.into_iter().all(|previous| self.read(entry, previous, prop))
+            }
+            Node::CommonKnowledge(group, prop) => {
+                let reachable = if group.is_empty() { HashSet::from([world]) } else { self.model.reachable_from(world).into_iter().collect() };
+                reachable.into_iter().all(|other| self.read(entry, other, prop))
+            }
+            Node::Counterfactual(antecedent, consequent) => {
+                let successors: Vec<World> = self.model.successors(world).collect();
+                successors.into_iter().all(|next| !self.read(entry, next, antecedent) || self.read(entry, next, consequent))
+            }
+        };
+        self.cache.insert(entry, value);
+        value
+    }
+
+    // Reads `node` at `world` on behalf of `reader`, recording that `reader` depends on it.
+    fn read(&mut self, reader: Entry, world: World, node: usize) -> bool {
+        let value = self.value(world, node);
+        self.dependents.entry((world, node)).or_default().insert(reader);
+        value
+    }
+}
+
+fn compile(prop: &Proposition, nodes: &mut Vec<Node>) -> usize {
+    let node = match prop {
+        Proposition::Atom(Atom::True) => Node::True,
+        Proposition::Atom(Atom::False) => Node::False,
+        Proposition::Atom(Atom::Variable(name)) => Node::Variable(*name),
+        Proposition::Atom(Atom::Nominal(name)) => Node::Nominal(*name),
+        Proposition::Connective(Connective::And(left, right)) => Node::And(compile(left, nodes), compile(right, nodes)),
+        Proposition::Connective(Connective::Or(left, right)) => Node::Or(compile(left, nodes), compile(right, nodes)),
+        Proposition::Connective(Connective::IfThen(left, right)) => Node::IfThen(compile(left, nodes), compile(right, nodes)),
+        Proposition::Connective(Connective::Iff(left, right)) => Node::Iff(compile(left, nodes), compile(right, nodes)),
+        Proposition::Connective(Connective::Not(prop)) => Node::Not(compile(prop, nodes)),
+        Proposition::Connective(Connective::Possibly(prop)) => Node::Possibly(compile(prop, nodes)),
+        Proposition::Connective(Connective::Necessarily(prop)) => Node::Necessarily(compile(prop, nodes)),
+        Proposition::Connective(Connective::Everywhere(prop)) => Node::Everywhere(compile(prop, nodes)),
+        Proposition::Connective(Connective::Somewhere(prop)) => Node::Somewhere(compile(prop, nodes)),
+        Proposition::Connective(Connective::At(name, prop)) => Node::At(*name, compile(prop, nodes)),
+        Proposition::Connective(Connective::Elsewhere(prop)) => Node::Elsewhere(compile(prop, nodes)),
+        Proposition::Connective(Connective::Counterfactual(left, right)) => Node::Counterfactual(compile(left, nodes), compile(right, nodes)),
+        Proposition::Connective(Connective::Knows(agent, prop)) => Node::Knows(*agent, compile(prop, nodes)),
+        Proposition::Connective(Connective::Next(prop)) => Node::Next(compile(prop, nodes)),
+        Proposition::Connective(Connective::Previous(prop)) => Node::Previous(compile(prop, nodes)),
+        Proposition::Connective(Connective::CommonKnowledge(group, prop)) => Node::CommonKnowledge(*group, compile(prop, nodes)),
+        Proposition::Parenthesised(prop) => return compile(prop, nodes),
+    };
+    nodes.push(node);
+    nodes.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate_at;
+
+    fn two_chains() -> Model {
+        let mut model = Model::new();
+        model.add_world(1, &['P']);
+        model.add_world(3, &[]);
+        model.add_edge(0, 1);
+        model.add_edge(2, 3);
+        model
+    }
+
+    #[test]
+    fn recomputes_only_entries_depending_on_an_edit() {
+        let prop = Proposition::or(Proposition::possibly(Proposition::variable('P')), Proposition::variable('Q'));
+        let mut compiled = CompiledFormula::new(two_chains(), &prop);
+        let worlds: Vec<World> = compiled.model().worlds().collect();
+        let before: Vec<bool> = worlds.iter().map(|world| compiled.evaluate(*world)).collect();
+        assert_eq!(before, vec![true, false, false, false]);
+
+        let misses = compiled.misses();
+        for world in &worlds {
+            compiled.evaluate(*world);
+        }
+        assert_eq!(compiled.misses(), misses);
+
+        // Only P at 3, ◇P at 2 and the disjunction at 2 read the edited atom.
+        compiled.set_atom(3, 'P', true);
+        let hits = compiled.hits();
+        let after: Vec<bool> = worlds.iter().map(|world| compiled.evaluate(*world)).collect();
+        assert_eq!(after, vec![true, false, true, false]);
+        assert_eq!(compiled.misses() - misses, 3);
+        assert!(compiled.hits() > hits);
+    }
+
+    #[test]
+    fn agrees_with_direct_evaluation_after_edge_edits() {
+        let p = Proposition::variable('P');
+        for prop in [
+            Proposition::necessarily(p.clone()),
+            Proposition::previous(Proposition::negation(p.clone())),
+            Proposition::common_knowledge(Group::new(&['a']), p),
+        ] {
+            let mut compiled = CompiledFormula::new(two_chains(), &prop);
+            for world in compiled.model().worlds().collect::<Vec<_>>() {
+                compiled.evaluate(world);
+            }
+            compiled.add_edge(2, 1);
+            compiled.remove_edge(0, 1);
+            compiled.add_edge(0, 3);
+            for world in compiled.model().worlds().collect::<Vec<_>>() {
+                assert_eq!(compiled.evaluate(world), evaluate_at(compiled.model(), world, &prop), "{} at {}", prop, world);
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::model::{evaluate_in, Semantics};
+use crate::{Group, Model, Proposition, World};
+
+/// An interpreted system for reasoning about knowledge over time: a `Model` whose worlds are global
+/// states and whose accessibility relation is the temporal one, read by `□` and `◇`, together with each
+/// agent's local states. `K_a φ` holds at a state if φ holds at every state in which agent `a` has the
+/// same local state, this one included, and `C_G φ` if φ holds at every state in `group_reachable`.
+#[derive(Debug, Clone, Default)]
+pub struct InterpretedSystem {
+    model: Model,
+    // Each agent's local states, as the classes of global states it can't tell apart.
+    local_states: BTreeMap<char, Vec<BTreeSet<World>>>,
+}
+
+impl InterpretedSystem {
+    /// An interpreted system over `model` in which every agent can tell every state apart until told otherwise.
+    pub fn new(model: Model) -> InterpretedSystem {
+        InterpretedSystem { model, local_states: BTreeMap::new() }
+    }
+
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Gives `agent` one local state for each of `classes`, shared by the states in it. States in no
+    /// class have a local state of their own.
+    pub fn set_local_states(&mut self, agent: char, classes: &[&[World]]) {
+        self.local_states.insert(agent, classes.iter().map(|class| class.iter().copied().collect()).collect());
+    }
+
+    /// The states `agent` can't tell apart from `world`, `world` among them.
+    pub fn indistinguishable(&self, agent: char, world: World) -> BTreeSet<World> {
+        self.local_states
+            .get(&agent)
+            .and_then(|classes| classes.iter().find(|class| class.contains(&world)))
+            .cloned()
+            .unwrap_or_else(|| BTreeSet::from([world]))
+    }
+
+    /// The states reachable from `world` through any chain of states that agents of `group` can't tell
+    /// apart, `world` among them: the reflexive-transitive closure of the union of their relations.
+    pub fn group_reachable(&self, group: Group, world: World) -> BTreeSet<World> {
+        let mut reachable = BTreeSet::from([world]);
+        let mut frontier = vec![world];
+        while let Some(world) = frontier.pop() {
+            for agent in group.agents() {
+                for next in self.indistinguishable(agent, world) {
+                    if reachable.insert(next) {
+                        frontier.push(next);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Evaluates `prop` at `world`, with `K_a` read off the local states and the rest as in `evaluate_at`.
+    pub fn evaluate(&self, world: World, prop: &Proposition) -> bool {
+        evaluate_in(self, world, prop)
+    }
+}
+
+impl Semantics for InterpretedSystem {
+    fn model(&self) -> &Model {
+        &self.model
+    }
+
+    fn knows(&self, agent: char, world: World, prop: &Proposition) -> bool {
+        self.indistinguishable(agent, world).into_iter().all(|other| self.evaluate(other, prop))
+    }
+
+    fn common_knowledge(&self, group: Group, world: World, prop: &Proposition) -> bool {
+        self.group_reachable(group, world).into_iter().all(|other| self.evaluate(other, prop))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    // Two runs, 0 → 1 and 3 → 4, reach P, but agent a confuses where they end up with world 5, where P
+    // fails. In a third run 6 → 8 the agent learns P, though at 6 it can't rule out the dead end 7.
+    fn system() -> InterpretedSystem {
+        let mut model = Model::new();
+        for world in [0, 3, 5, 6, 7] {
+            model.add_world(world, &[]);
+        }
+        for world in [1, 4, 8] {
+            model.add_world(world, &['P']);
+        }
+        for (from, to) in [(0, 1), (3, 4), (6, 8)] {
+            model.add_edge(from, to);
+        }
+        let mut system = InterpretedSystem::new(model);
+        system.set_local_states('a', &[&[0, 3], &[1, 4, 5], &[6, 7]]);
+        system
+    }
+
+    #[test]
+    fn knowing_the_future_differs_from_future_knowledge() {
+        let system = system();
+        let knows_eventually = parse_formula("K_a ◇P").unwrap();
+        let eventually_knows = parse_formula("◇K_a P").unwrap();
+        assert!(system.evaluate(0, &knows_eventually));
+        assert!(!system.evaluate(0, &eventually_knows));
+        assert!(!system.evaluate(6, &knows_eventually));
+        assert!(system.evaluate(6, &eventually_knows));
+    }
+
+    #[test]
+    fn knowledge_is_relative_to_local_states() {
+        let system = system();
+        assert_eq!(system.indistinguishable('a', 4), BTreeSet::from([1, 4, 5]));
+        assert_eq!(system.indistinguishable('b', 4), BTreeSet::from([4]));
+        assert!(system.evaluate(4, &parse_formula("K_b P").unwrap()));
+        assert!(!system.evaluate(4, &parse_formula("K_a P").unwrap()));
+        // b knows P at 4, but a can't rule out 5, where b knows no such thing.
+        assert!(system.evaluate(4, &parse_formula("¬K_a K_b P ∧ ¬K_a ¬K_b P").unwrap()));
+        assert_eq!(parse_formula("K_a ◇K_b P").unwrap().to_string(), "K_a ◇K_b P");
+        assert!(parse_formula("K P").is_err());
+    }
+
+    #[test]
+    fn common_knowledge_needs_every_level_of_mutual_knowledge() {
+        // a can't tell 0 from 1, and b can't tell 1 from 2, where P fails.
+        let mut model = Model::new();
+        model.add_world(0, &['P']);
+        model.add_world(1, &['P']);
+        model.add_world(2, &[]);
+        let mut system = InterpretedSystem::new(model);
+        system.set_local_states('a', &[&[0, 1]]);
+        system.set_local_states('b', &[&[1, 2]]);
+
+        assert!(system.evaluate(0, &parse_formula("K_a P ∧ K_b P").unwrap()));
+        assert!(!system.evaluate(0, &parse_formula("K_a K_b P").unwrap()));
+        assert_eq!(system.group_reachable(Group::new(&['a', 'b']), 0), BTreeSet::from([0, 1, 2]));
+        assert!(!system.evaluate(0, &parse_formula("C_{ab} P").unwrap()));
+        assert!(system.evaluate(0, &parse_formula("C_{a} P").unwrap()));
+        assert!(system.evaluate(0, &parse_formula("C_{} P").unwrap()));
+        // b can't rule out 2 at 1 either, so at 2 b knows P isn't common knowledge.
+        assert!(system.evaluate(2, &parse_formula("K_b ¬C_{ab} P").unwrap()));
+        assert!(system.evaluate(0, &parse_formula("K_a C_{a} P ∧ ¬K_a C_{ab} P").unwrap()));
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+use crate::{propositional_skeleton, simplify, substitute, Atom, Connective, Model, Proposition, World};
+
+/// A model satisfying `prop` at world 0 in modal logic K, or `None` if no Kripke model does.
+///
+/// This is the K-SAT procedure: the propositional skeleton of the formulas a world must satisfy is
+/// solved, and each solution sets which `□ψ` and `◇ψ` hold there. Every `◇ψ` that holds then needs a
+/// successor satisfying ψ and every boxed formula, found the same way; a solution whose demands can't
+/// all be met is abandoned for the next. The skeleton is solved by splitting on one atom at a time,
+/// taking the atoms it forces first, and a solution leaves unset the atoms it doesn't depend on, so
+/// their modal formulas demand nothing. The formulas shrink in modal depth at each step, so the search
+/// ends, and builds a tree-shaped model only as wide as the demands.
+///
+/// `K_a φ` is read as `□φ`, `X φ` as `◇⊤ ∧ □φ` and `φ □→ ψ` as `□(φ → ψ)`, as `evaluate_at` reads them.
+/// The global operators, `Y`, `C_G` and nominals aren't local to a world's successors, so formulas with them
+/// are an `Error::NotBasicModal`.
+pub fn modal_sat_k(prop: &Proposition) -> Result<Option<Model>> {
+    let mut model = Model::new();
+    Ok(satisfy(&[basic(prop)?], &mut model).map(|_| model))
+}
+
+// Adds a world satisfying every formula of `props` to `model`, with the successors it needs.
+fn satisfy(props: &[Proposition], model: &mut Model) -> Option<World> {
+    let (skeleton, abstracted) = propositional_skeleton(&Proposition::conjunction(props.iter().cloned()));
+    let abstracted: BTreeMap<char, Proposition> = abstracted.into_iter().collect();
+    let mut satisfied = None;
+    solve(&simplify(&skeleton), &mut Vec::new(), &mut |assignment| {
+        let (mut boxed, mut demands) = (Vec::new(), Vec::new());
+        for (atom, value) in assignment {
+            match (abstracted.get(atom), value) {
+                (Some(Proposition::Connective(Connective::Necessarily(prop))), true) => boxed.push((**prop).clone()),
+                (Some(Proposition::Connective(Connective::Necessarily(prop))), false) => demands.push(Proposition::negation((**prop).clone())),
+                (Some(Proposition::Connective(Connective::Possibly(prop))), true) => demands.push((**prop).clone()),
+                (Some(Proposition::Connective(Connective::Possibly(prop))), false) => boxed.push(Proposition::negation((**prop).clone())),
+                (Some(_), _) => unreachable!("only □ and ◇ are left after `basic`"),
+                (None, _) => (),
+            }
+        }
+        let mut candidate = model.clone();
+        let world = candidate.worlds().count();
+        let true_atoms: Vec<char> = assignment.iter().filter(|(atom, value)| *value && !abstracted.contains_key(atom)).map(|(atom, _)| *atom).collect();
+        candidate.add_world(world, &true_atoms);
+        let met = demands.into_iter().all(|demand| match satisfy(&[boxed.clone(), vec![demand]].concat(), &mut candidate) {
+            Some(successor) => {
+                candidate.add_edge(world, successor);
+                true
+            }
+            None => false,
+        });
+        if met {
+            *model = candidate;
+            satisfied = Some(world);
+        }
+        met
+    });
+    satisfied
+}
+
+// Values for some of a skeleton's atoms, in the order they were set.
+type Assignment = Vec<(char, bool)>;
+
+// Hands each partial assignment extending `assigned` that makes the simplified formula `prop` true to
+// `found`, until it accepts one, and says whether it did.
+fn solve(prop: &Proposition, assigned: &mut Assignment, found: &mut dyn FnMut(&Assignment) -> bool) -> bool {
+    let (atom, values) = match prop {
+        Proposition::Atom(Atom::True) => return found(assigned),
+        Proposition::Atom(Atom::False) => return false,
+        _ => match forced(prop) {
+            Some((atom, value)) => (atom, vec![value]),
+            None => (first_atom(prop), vec![true, false]),
+        },
+    };
+    for value in values {
+        assigned.push((atom, value));
+        if solve(&simplify(&substitute(prop, atom, &Proposition::constant(value))), assigned, found) {
+            return true;
+        }
+        assigned.pop();
+    }
+    false
+}
+
+// An atom and the value a conjunct of `prop` that is a literal forces on it.
+fn forced(prop: &Proposition) -> Option<(char, bool)> {
+    let mut conjuncts = vec![prop];
+    while let Some(conjunct) = conjuncts.pop() {
+        match conjunct {
+            Proposition::Connective(Connective::And(left, right)) => conjuncts.extend([&**left, &**right]),
+            Proposition::Atom(Atom::Variable(atom)) => return Some((*atom, true)),
+            Proposition::Connective(Connective::Not(negated)) => {
+                if let Proposition::Atom(Atom::Variable(atom)) = **negated {
+                    return Some((atom, false));
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+fn first_atom(prop: &Proposition) -> char {
+    let mut pending = vec![prop];
+    while let Some(prop) = pending.pop() {
+        if let Proposition::Atom(Atom::Variable(atom)) = prop {
+            return *atom;
+        }
+        pending.extend(prop.operands().into_iter().rev());
+    }
+    unreachable!("a skeleton that isn't constant has an atom")
+}
+
+// `prop` with `K_a`, `X` and `□→` rewritten in terms of `□` and `◇`.
+fn basic(prop: &Proposition) -> Result<Proposition> {
+    let operands = prop.operands().into_iter().map(basic).collect::<Result<Vec<Proposition>>>()?;
+    Ok(match prop {
+        Proposition::Atom(Atom::Nominal(_))
+        | Proposition::Connective(Connective::Everywhere(_) | Connective::Somewhere(_) | Connective::At(..) | Connective::Elsewhere(_) | Connective::Previous(_) | Connective::CommonKnowledge(..)) => {
+            return Err(Error::NotBasicModal)
+        }
+        Proposition::Connective(Connective::Knows(..)) => Proposition::necessarily(operands[0].clone()),
+        Proposition::Connective(Connective::Next(_)) => Proposition::and(Proposition::possibly(Proposition::constant(true)), Proposition::necessarily(operands[0].clone())),
+        Proposition::Connective(Connective::Counterfactual(..)) => Proposition::necessarily(Proposition::if_then(operands[0].clone(), operands[1].clone())),
+        _ => prop.with_operands(operands),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{evaluate_at, parse_enriched_formula, parse_formula};
+
+    #[test]
+    fn builds_models_for_satisfiable_formulas() {
+        for formula in ["◇P ∧ ◇¬P ∧ □Q", "□(P → ◇Q) ∧ ◇P ∧ ¬Q", "¬□□P ∧ □◇(P ∨ ¬P)", "K_a P ∧ (Q □→ R) ∧ ◇(Q ∧ P)"] {
+            let prop = parse_formula(formula).unwrap();
+            let model = modal_sat_k(&prop).unwrap().unwrap_or_else(|| panic!("{} is satisfiable", formula));
+            assert!(evaluate_at(&model, 0, &prop), "{}", formula);
+        }
+        let model = modal_sat_k(&parse_formula("◇P ∧ ◇¬P").unwrap()).unwrap().unwrap();
+        assert_eq!(model.successors(0).count(), 2);
+    }
+
+    #[test]
+    fn rejects_k_unsatisfiable_formulas() {
+        for formula in ["□P ∧ ◇¬P", "◇◇P ∧ □□¬P", "◇(P ∧ ¬P)", "(□(P → Q)) ∧ □P ∧ ◇¬Q", "K_a P ∧ ◇¬P"] {
+            assert_eq!(modal_sat_k(&parse_formula(formula).unwrap()), Ok(None), "{}", formula);
+        }
+        // T's axiom fails in K.
+        assert!(modal_sat_k(&parse_formula("□P ∧ ¬P").unwrap()).unwrap().is_some());
+    }
+
+    #[test]
+    fn solves_many_modal_subformulas() {
+        // Sixty diamonds over P, Q, R and S, each needing a successor of its own.
+        let diamonds: Vec<Proposition> = (0..60)
+            .map(|index| {
+                let atoms = ['P', 'Q', 'R', 'S'].map(Proposition::variable);
+                let bits = atoms.iter().enumerate().map(|(bit, atom)| if index >> bit & 1 == 1 { atom.clone() } else { Proposition::negation(atom.clone()) });
+                Proposition::possibly((0..index / 16).fold(Proposition::conjunction(bits), |prop, _| Proposition::possibly(prop)))
+            })
+            .collect();
+        let prop = Proposition::conjunction(diamonds);
+        let model = modal_sat_k(&prop).unwrap().unwrap();
+        assert!(evaluate_at(&model, 0, &prop));
+        assert_eq!(model.successors(0).count(), 60);
+    }
+
+    #[test]
+    fn rejects_formulas_outside_the_basic_language() {
+        for formula in ["◇i", "A P", "@i □P", "Y P", "C_{ab} P"] {
+            assert_eq!(modal_sat_k(&parse_enriched_formula(formula).unwrap()), Err(Error::NotBasicModal), "{}", formula);
+        }
+    }
+}
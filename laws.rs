@@ -0,0 +1,95 @@
+use crate::{equivalent, Proposition};
+
+/// Equivalence laws of propositional logic, and some common fallacies to contrast them with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LawName {
+    /// `a ∧ (b ∨ c) ≡ (a ∧ b) ∨ (a ∧ c)`
+    AndDistributesOverOr,
+    /// `a ∨ (b ∧ c) ≡ (a ∨ b) ∧ (a ∨ c)`
+    OrDistributesOverAnd,
+    /// `a ∧ (a ∨ b) ≡ a`
+    AndAbsorption,
+    /// `a ∨ (a ∧ b) ≡ a`
+    OrAbsorption,
+    /// `¬(a ∧ b) ≡ ¬a ∨ ¬b`
+    DeMorganAnd,
+    /// `¬(a ∨ b) ≡ ¬a ∧ ¬b`
+    DeMorganOr,
+    /// `¬¬a ≡ a`
+    DoubleNegation,
+    /// `a → b ≡ ¬b → ¬a`
+    Contraposition,
+    /// `(a ∧ b) → c ≡ a → (b → c)`
+    Exportation,
+    /// Not a law: `a → b ≡ b → a`, confusing a conditional with its converse.
+    Converse,
+    /// Not a law: `¬(a ∧ b) ≡ ¬a ∧ ¬b`, a common misremembering of De Morgan's law.
+    NegationDistributesOverAnd,
+}
+
+impl LawName {
+    /// How many formulas an instance of the law substitutes for its `a`, `b`, ….
+    pub fn arity(&self) -> usize {
+        match self {
+            LawName::DoubleNegation => 1,
+            LawName::AndAbsorption | LawName::OrAbsorption | LawName::DeMorganAnd | LawName::DeMorganOr | LawName::Contraposition => 2,
+            LawName::Converse | LawName::NegationDistributesOverAnd => 2,
+            LawName::AndDistributesOverOr | LawName::OrDistributesOverAnd | LawName::Exportation => 3,
+        }
+    }
+
+    /// The two sides of the law with `formulas` substituted for `a`, `b`, … in order.
+    pub fn instance(&self, formulas: &[Proposition]) -> (Proposition, Proposition) {
+        assert_eq!(formulas.len(), self.arity(), "{:?} takes {} formulas", self, self.arity());
+        let [a, b, c] = [0, 1, 2].map(|index| move || formulas[index].clone());
+        let not = Proposition::negation;
+        match self {
+            LawName::AndDistributesOverOr => (Proposition::and(a(), Proposition::or(b(), c())), Proposition::or(Proposition::and(a(), b()), Proposition::and(a(), c()))),
+            LawName::OrDistributesOverAnd => (Proposition::or(a(), Proposition::and(b(), c())), Proposition::and(Proposition::or(a(), b()), Proposition::or(a(), c()))),
+            LawName::AndAbsorption => (Proposition::and(a(), Proposition::or(a(), b())), a()),
+            LawName::OrAbsorption => (Proposition::or(a(), Proposition::and(a(), b())), a()),
+            LawName::DeMorganAnd => (not(Proposition::and(a(), b())), Proposition::or(not(a()), not(b()))),
+            LawName::DeMorganOr => (not(Proposition::or(a(), b())), Proposition::and(not(a()), not(b()))),
+            LawName::DoubleNegation => (not(not(a())), a()),
+            LawName::Contraposition => (Proposition::if_then(a(), b()), Proposition::if_then(not(b()), not(a()))),
+            LawName::Exportation => (Proposition::if_then(Proposition::and(a(), b()), c()), Proposition::if_then(a(), Proposition::if_then(b(), c()))),
+            LawName::Converse => (Proposition::if_then(a(), b()), Proposition::if_then(b(), a())),
+            LawName::NegationDistributesOverAnd => (not(Proposition::and(a(), b())), Proposition::and(not(a()), not(b()))),
+        }
+    }
+}
+
+/// Whether the instance of `law` with `formulas` substituted for its `a`, `b`, … is an equivalence, as
+/// decided by `equivalent`. Panics if the number of formulas isn't the law's `arity`.
+pub fn check_law(law: LawName, formulas: &[Proposition]) -> bool {
+    let (left, right) = law.instance(formulas);
+    equivalent(&left, &right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    fn formulas(sources: &[&str]) -> Vec<Proposition> {
+        sources.iter().map(|source| parse_formula(source).unwrap()).collect()
+    }
+
+    #[test]
+    fn laws_hold_for_every_instance() {
+        assert!(check_law(LawName::AndDistributesOverOr, &formulas(&["P → Q", "¬R", "Q ↔ S"])));
+        assert!(check_law(LawName::OrDistributesOverAnd, &formulas(&["P", "Q", "R"])));
+        assert!(check_law(LawName::DeMorganOr, &formulas(&["P ∧ Q", "¬P"])));
+        assert!(check_law(LawName::Exportation, &formulas(&["P", "Q ∨ R", "S"])));
+        let (left, right) = LawName::AndAbsorption.instance(&formulas(&["P", "Q"]));
+        assert_eq!((left.to_string(), right.to_string()), ("P ∧ (P ∨ Q)".to_string(), "P".to_string()));
+    }
+
+    #[test]
+    fn fallacies_fail() {
+        assert!(!check_law(LawName::Converse, &formulas(&["P", "Q"])));
+        assert!(!check_law(LawName::NegationDistributesOverAnd, &formulas(&["P", "Q"])));
+        // A fallacy can still have instances that happen to be equivalences.
+        assert!(check_law(LawName::Converse, &formulas(&["P", "P"])));
+    }
+}
@@ -1,4 +1,71 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+mod argument;
+mod batch;
+mod bdd;
+mod bisimulation;
+mod cnf;
+mod context;
+mod correspondence;
+mod dot;
+mod epistemic;
+mod error;
+mod fixture;
+mod fragment;
+mod hilbert;
+mod html;
+mod incremental;
+mod interpreted;
+mod ksat;
+mod laws;
+mod matrix;
+mod model;
+mod order;
+mod position;
+mod prime;
+mod proof;
+mod random;
+mod report;
+mod rewrite;
+mod rpn;
+mod sat;
+mod search;
+mod sphere;
+mod submodel;
+mod truth_table;
+
+pub use argument::{Consequence, ModalArgument, Validity};
+pub use batch::{evaluate_batch, FormulaBatch};
+pub use bdd::{Bdd, ReorderStrategy};
+pub use bisimulation::{bisimilar, bisimulation_contraction, characteristic_formula, distinguishing_depth, has_modal_collapse, modally_equivalent_up_to};
+pub use cnf::{from_cnf, literals, simplify_cnf, to_cnf, to_cnf_with, Clause, Literal};
+pub use context::{evaluate_under, Context, Judgement};
+pub use correspondence::{frame_correspondent, is_sahlqvist, FoCondition, FoVariable};
+pub use epistemic::only_knows;
+pub use error::{Error, ParseError, Result};
+pub use fixture::{Case, Fixture, Outcome};
+pub use fragment::{fragment, Fragment};
+pub use hilbert::AxiomSystem;
+pub use incremental::CompiledFormula;
+pub use interpreted::InterpretedSystem;
+pub use ksat::modal_sat_k;
+pub use laws::{check_law, LawName};
+pub use model::{evaluate_at, evaluate_at_set, evaluate_at_with, EvaluationOptions, Model, TruthMode, World};
+pub use order::AtomOrder;
+pub use position::positions;
+pub use prime::{distinct_functions, from_truth_function, from_truth_function_with_dc, minimize_cnf, prime_implicants, prime_implicates};
+pub use proof::{Proof, Step};
+pub use report::{analyze, Classification, Report};
+pub use rewrite::{boolean_derivative, polarity_map, propositional_skeleton, simplify, simplify_with, substitute, to_nnf, to_nnf_with, Polarity, TransformOptions};
+pub use rpn::{eval_rpn, from_rpn, to_rpn, Token};
+pub use sat::{consistent_model, entails, equivalent, horn_sat, is_consistent, minimal_unsat_subset, model_count, output_entropy, probability_interval, redundant_premises, weighted_model_count};
+pub use search::{find_countermodel, is_valid_in, minimize_countermodel, modally_equivalent, sample_countermodel, FrameFamily, System};
+pub use sphere::SphereModel;
+pub use submodel::{preserved_under_submodels, SubmodelKind};
+pub use truth_table::{Row, TruthTable};
 
 pub fn evaluate_propositional_string(prop: &str) -> bool {
     let proposition = parse_proposition_string(prop);
@@ -10,6 +77,8 @@ fn evaluate(expression: Proposition) -> bool {
     match expression {
         Proposition::Atom(Atom::True) => true,
         Proposition::Atom(Atom::False) => false,
+        Proposition::Atom(Atom::Variable(name)) => panic!("Unassigned atom {}", name),
+        Proposition::Atom(Atom::Nominal(name)) => panic!("Nominal {} names no world", name),
         Proposition::Connective(Connective::And(left, right)) => evaluate(*left) && evaluate(*right),
         Proposition::Connective(Connective::Or(left, right)) => evaluate(*left) || evaluate(*right),
         Proposition::Connective(Connective::IfThen(left, right)) => !evaluate(*left) || evaluate(*right),
@@ -25,25 +94,40 @@ fn evaluate(expression: Proposition) -> bool {
             // for all worlds related to the actual, the proposition is true
             evaluate(*prop)
         }
+        // With only one valuation, there's only one world for the global modalities to range over.
+        Proposition::Connective(Connective::Everywhere(prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::Somewhere(prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::At(_, prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::Elsewhere(_)) => false,
+        // The closest antecedent world is the only one.
+        Proposition::Connective(Connective::Counterfactual(antecedent, consequent)) => !evaluate(*antecedent) || evaluate(*consequent),
+        Proposition::Connective(Connective::Knows(_, prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::Next(prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::Previous(prop)) => evaluate(*prop),
+        Proposition::Connective(Connective::CommonKnowledge(_, prop)) => evaluate(*prop),
         Proposition::Parenthesised(prop) => evaluate(*prop),
     }
 }
 
-#[derive(Debug, Clone)]
-enum Proposition {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Proposition {
     Atom(Atom),
     Connective(Connective),
     Parenthesised(Box<Proposition>),
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Atom {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Atom {
     True,
     False,
+    /// An atom left unassigned, whose value comes from a world of a `Model`.
+    Variable(char),
+    /// A nominal, true at exactly the world of a `Model` it names.
+    Nominal(char),
 }
 
-#[derive(Debug, Clone)]
-enum Connective {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Connective {
     And(Box<Proposition>, Box<Proposition>),
     Or(Box<Proposition>, Box<Proposition>),
     IfThen(Box<Proposition>, Box<Proposition>),
@@ -51,94 +135,626 @@ enum Connective {
     Not(Box<Proposition>),
     Possibly(Box<Proposition>),
     Necessarily(Box<Proposition>),
+    /// The universal modality `A`: true at every world of the model, accessible or not.
+    Everywhere(Box<Proposition>),
+    /// The existential modality `E`: true at some world of the model.
+    Somewhere(Box<Proposition>),
+    /// `@i φ`: φ is true at the world named by the nominal `i`.
+    At(char, Box<Proposition>),
+    /// The difference modality `D`: true at some world other than this one.
+    Elsewhere(Box<Proposition>),
+    /// The counterfactual `φ □→ ψ`: ψ holds at the closest worlds where φ does. Without a similarity
+    /// ordering, as in a plain `Model`, the accessible worlds count as equally close.
+    Counterfactual(Box<Proposition>, Box<Proposition>),
+    /// `K_a φ`: agent `a` knows φ, i.e. φ holds at every world `a` can't tell apart from this one.
+    /// Without relations of their own for the agents, as in a plain `Model`, this is `□φ`.
+    Knows(char, Box<Proposition>),
+    /// `X φ`: φ holds at the next world, reading the accessibility relation as a successor function.
+    /// False at a world with no successor.
+    Next(Box<Proposition>),
+    /// `Y φ`: φ holds at the previous world, the one this world is the successor of. False at a world
+    /// with no predecessor.
+    Previous(Box<Proposition>),
+    /// `C_{ab} φ`: φ is common knowledge among the group: everyone in it knows φ, everyone knows that
+    /// everyone knows it, and so on up. That's φ at every world reachable from this one, itself
+    /// included, along the relations of the group's agents; in a plain `Model` each is the
+    /// accessibility relation, as for `K_a`.
+    CommonKnowledge(Group, Box<Proposition>),
+}
+
+/// A group of agents, each a lowercase ASCII letter as in `K_a`, among whom `C_G` is common knowledge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Group(u32);
+
+impl Group {
+    /// # Panics
+    ///
+    /// If an agent isn't a lowercase ASCII letter.
+    pub fn new(agents: &[char]) -> Group {
+        Group(agents.iter().fold(0, |bits, agent| {
+            assert!(agent.is_ascii_lowercase(), "agents are lowercase ASCII letters, not {}", agent);
+            bits | 1 << (*agent as u32 - 'a' as u32)
+        }))
+    }
+
+    /// The agents in alphabetical order.
+    pub fn agents(&self) -> impl Iterator<Item = char> + '_ {
+        ('a'..='z').filter(|agent| self.contains(*agent))
+    }
+
+    pub fn contains(&self, agent: char) -> bool {
+        agent.is_ascii_lowercase() && self.0 & 1 << (agent as u32 - 'a' as u32) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.agents().try_for_each(|agent| write!(f, "{}", agent))
+    }
+}
+
+// Constructors for building formulas directly, e.g. modal formulas the string parser can't express yet.
+impl Proposition {
+    pub fn constant(value: bool) -> Proposition {
+        Proposition::Atom(if value { Atom::True } else { Atom::False })
+    }
+
+    pub fn variable(name: char) -> Proposition {
+        Proposition::Atom(Atom::Variable(name))
+    }
+
+    pub fn and(left: Proposition, right: Proposition) -> Proposition {
+        Proposition::Connective(Connective::And(Box::new(left), Box::new(right)))
+    }
+
+    pub fn or(left: Proposition, right: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Or(Box::new(left), Box::new(right)))
+    }
+
+    pub fn if_then(left: Proposition, right: Proposition) -> Proposition {
+        Proposition::Connective(Connective::IfThen(Box::new(left), Box::new(right)))
+    }
+
+    pub fn iff(left: Proposition, right: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Iff(Box::new(left), Box::new(right)))
+    }
+
+    pub fn negation(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Not(Box::new(prop)))
+    }
+
+    pub fn possibly(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Possibly(Box::new(prop)))
+    }
+
+    pub fn necessarily(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Necessarily(Box::new(prop)))
+    }
+
+    pub fn everywhere(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Everywhere(Box::new(prop)))
+    }
+
+    pub fn somewhere(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Somewhere(Box::new(prop)))
+    }
+
+    pub fn nominal(name: char) -> Proposition {
+        Proposition::Atom(Atom::Nominal(name))
+    }
+
+    pub fn at(nominal: char, prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::At(nominal, Box::new(prop)))
+    }
+
+    pub fn elsewhere(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Elsewhere(Box::new(prop)))
+    }
+
+    pub fn counterfactual(antecedent: Proposition, consequent: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Counterfactual(Box::new(antecedent), Box::new(consequent)))
+    }
+
+    pub fn knows(agent: char, prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Knows(agent, Box::new(prop)))
+    }
+
+    pub fn next(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Next(Box::new(prop)))
+    }
+
+    pub fn previous(prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::Previous(Box::new(prop)))
+    }
+
+    pub fn common_knowledge(group: Group, prop: Proposition) -> Proposition {
+        Proposition::Connective(Connective::CommonKnowledge(group, Box::new(prop)))
+    }
+
+    /// Right-nested conjunction of `props`, or `⊤` if there are none.
+    pub fn conjunction(props: impl IntoIterator<Item = Proposition>) -> Proposition {
+        let props: Vec<Proposition> = props.into_iter().collect();
+        props.into_iter().rev().reduce(|right, left| Proposition::and(left, right)).unwrap_or(Proposition::constant(true))
+    }
+
+    /// Right-nested disjunction of `props`, or `⊥` if there are none.
+    pub fn disjunction(props: impl IntoIterator<Item = Proposition>) -> Proposition {
+        let props: Vec<Proposition> = props.into_iter().collect();
+        props.into_iter().rev().reduce(|right, left| Proposition::or(left, right)).unwrap_or(Proposition::constant(false))
+    }
+
+    /// The number of atoms and connectives, not counting parentheses.
+    pub fn size(&self) -> usize {
+        let own = if let Proposition::Parenthesised(_) = self { 0 } else { 1 };
+        own + self.operands().into_iter().map(Proposition::size).sum::<usize>()
+    }
+
+    /// The deepest nesting of `□`, `◇`, `□→`, `K_a` and `C_G`.
+    pub fn modal_depth(&self) -> usize {
+        let own = matches!(
+            self,
+            Proposition::Connective(Connective::Possibly(_) | Connective::Necessarily(_) | Connective::Counterfactual(..) | Connective::Knows(..) | Connective::CommonKnowledge(..))
+        ) as usize;
+        own + self.operands().into_iter().map(Proposition::modal_depth).max().unwrap_or(0)
+    }
+
+    pub(crate) fn operands(&self) -> Vec<&Proposition> {
+        match self {
+            Proposition::Atom(_) => vec![],
+            Proposition::Connective(Connective::And(left, right))
+            | Proposition::Connective(Connective::Or(left, right))
+            | Proposition::Connective(Connective::IfThen(left, right))
+            | Proposition::Connective(Connective::Iff(left, right))
+            | Proposition::Connective(Connective::Counterfactual(left, right)) => vec![left, right],
+            Proposition::Connective(Connective::Not(prop))
+            | Proposition::Connective(Connective::Possibly(prop))
+            | Proposition::Connective(Connective::Necessarily(prop))
+            | Proposition::Connective(Connective::Everywhere(prop))
+            | Proposition::Connective(Connective::Somewhere(prop))
+            | Proposition::Connective(Connective::At(_, prop))
+            | Proposition::Connective(Connective::Elsewhere(prop))
+            | Proposition::Connective(Connective::Knows(_, prop))
+            | Proposition::Connective(Connective::Next(prop))
+            | Proposition::Connective(Connective::Previous(prop))
+            | Proposition::Connective(Connective::CommonKnowledge(_, prop))
+            | Proposition::Parenthesised(prop) => vec![prop],
+        }
+    }
+
+    /// Rebuilds this node around new operands, given in the order `operands` returns them.
+    pub(crate) fn with_operands(&self, operands: Vec<Proposition>) -> Proposition {
+        let mut operands = operands.into_iter().map(Box::new);
+        let mut next = || operands.next().expect("missing operand");
+        match self {
+            Proposition::Atom(atom) => Proposition::Atom(*atom),
+            Proposition::Connective(Connective::And(..)) => Proposition::Connective(Connective::And(next(), next())),
+            Proposition::Connective(Connective::Or(..)) => Proposition::Connective(Connective::Or(next(), next())),
+            Proposition::Connective(Connective::IfThen(..)) => Proposition::Connective(Connective::IfThen(next(), next())),
+            Proposition::Connective(Connective::Iff(..)) => Proposition::Connective(Connective::Iff(next(), next())),
+            Proposition::Connective(Connective::Not(_)) => Proposition::Connective(Connective::Not(next())),
+            Proposition::Connective(Connective::Possibly(_)) => Proposition::Connective(Connective::Possibly(next())),
+            Proposition::Connective(Connective::Necessarily(_)) => Proposition::Connective(Connective::Necessarily(next())),
+            Proposition::Connective(Connective::Everywhere(_)) => Proposition::Connective(Connective::Everywhere(next())),
+            Proposition::Connective(Connective::Somewhere(_)) => Proposition::Connective(Connective::Somewhere(next())),
+            Proposition::Connective(Connective::At(nominal, _)) => Proposition::Connective(Connective::At(*nominal, next())),
+            Proposition::Connective(Connective::Elsewhere(_)) => Proposition::Connective(Connective::Elsewhere(next())),
+            Proposition::Connective(Connective::Counterfactual(..)) => Proposition::Connective(Connective::Counterfactual(next(), next())),
+            Proposition::Connective(Connective::Knows(agent, _)) => Proposition::Connective(Connective::Knows(*agent, next())),
+            Proposition::Connective(Connective::Next(_)) => Proposition::Connective(Connective::Next(next())),
+            Proposition::Connective(Connective::Previous(_)) => Proposition::Connective(Connective::Previous(next())),
+            Proposition::Connective(Connective::CommonKnowledge(group, _)) => Proposition::Connective(Connective::CommonKnowledge(*group, next())),
+            Proposition::Parenthesised(_) => Proposition::Parenthesised(next()),
+        }
+    }
+}
+
+/// The atoms (and nominals) occurring in `prop`.
+pub fn atoms(prop: &Proposition) -> BTreeSet<char> {
+    let mut atoms = BTreeSet::new();
+    let mut pending = vec![prop];
+    while let Some(prop) = pending.pop() {
+        if let Proposition::Atom(Atom::Variable(name) | Atom::Nominal(name)) = prop {
+            atoms.insert(*name);
+        }
+        pending.extend(prop.operands());
+    }
+    atoms
+}
+
+// Operands that are themselves binary connectives are bracketed, so the output never depends on precedence.
+impl fmt::Display for Proposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |prop: &Proposition| match prop {
+            Proposition::Connective(Connective::And(..) | Connective::Or(..) | Connective::IfThen(..) | Connective::Iff(..) | Connective::Counterfactual(..)) => {
+                format!("({})", prop)
+            }
+            _ => prop.to_string(),
+        };
+        match self {
+            Proposition::Atom(Atom::True) => write!(f, "⊤"),
+            Proposition::Atom(Atom::False) => write!(f, "⊥"),
+            Proposition::Atom(Atom::Variable(name) | Atom::Nominal(name)) => write!(f, "{}", name),
+            Proposition::Connective(Connective::And(left, right)) => write!(f, "{} ∧ {}", operand(left), operand(right)),
+            Proposition::Connective(Connective::Or(left, right)) => write!(f, "{} ∨ {}", operand(left), operand(right)),
+            Proposition::Connective(Connective::IfThen(left, right)) => write!(f, "{} → {}", operand(left), operand(right)),
+            Proposition::Connective(Connective::Iff(left, right)) => write!(f, "{} ↔ {}", operand(left), operand(right)),
+            Proposition::Connective(Connective::Not(prop)) => write!(f, "¬{}", operand(prop)),
+            Proposition::Connective(Connective::Possibly(prop)) => write!(f, "◇{}", operand(prop)),
+            Proposition::Connective(Connective::Necessarily(prop)) => write!(f, "□{}", operand(prop)),
+            Proposition::Connective(Connective::Everywhere(prop)) => write!(f, "A {}", operand(prop)),
+            Proposition::Connective(Connective::Somewhere(prop)) => write!(f, "E {}", operand(prop)),
+            Proposition::Connective(Connective::At(name, prop)) => write!(f, "@{} {}", name, operand(prop)),
+            Proposition::Connective(Connective::Elsewhere(prop)) => write!(f, "D {}", operand(prop)),
+            Proposition::Connective(Connective::Counterfactual(antecedent, consequent)) => write!(f, "{} □→ {}", operand(antecedent), operand(consequent)),
+            Proposition::Connective(Connective::Knows(agent, prop)) => write!(f, "K_{} {}", agent, operand(prop)),
+            Proposition::Connective(Connective::Next(prop)) => write!(f, "X {}", operand(prop)),
+            Proposition::Connective(Connective::Previous(prop)) => write!(f, "Y {}", operand(prop)),
+            Proposition::Connective(Connective::CommonKnowledge(group, prop)) => write!(f, "C_{{{}}} {}", group, operand(prop)),
+            Proposition::Parenthesised(prop) => write!(f, "({})", prop),
+        }
+    }
+}
+
+/// Parses a bare formula such as `□(P → Q)`. Atoms are left as variables to be valued by a `Model`.
+/// Whitespace, newlines included, only separates tokens, so a long formula can be broken across lines.
+///
+/// Prefix operators (`¬`, `◇`, `□`) apply only to the atom, parenthesised group or prefixed formula right
+/// after them, so `¬P ∧ Q` is `(¬P) ∧ Q`. The binary connectives bind in the order `∧`, `∨`, `→`, `↔`,
+/// tightest first: `P ∧ Q ∨ R → S` is `((P ∧ Q) ∨ R) → S`. `∧` and `∨` group to the left, and `→` and
+/// `↔` to the right.
+///
+/// The counterfactual `φ □→ ψ` binds and groups like `→`, and the two can be mixed: `P □→ Q → R` is
+/// `P □→ (Q → R)`. `K_a φ`, agent `a`'s knowledge, is a prefix operator for any lowercase agent `a`, and
+/// so is `C_{ab} φ`, common knowledge among the agents between the braces.
+///
+/// `□*φ` and `◇*φ`, the reflexive closures of `□` and `◇`, are read as `φ ∧ □φ` and `φ ∨ ◇φ`: φ holds at
+/// this world and every accessible one, or at this world or some accessible one.
+pub fn parse_formula(source: &str) -> Result<Proposition> {
+    try_parse_proposition(source, &HashMap::new(), false, 0)
+}
+
+/// Parses a formula of the enriched modal language, which extends `parse_formula`'s grammar with
+///
+/// - `A φ` and `E φ`: φ holds at every / some world of the model (the universal modality),
+/// - `D φ`: φ holds at some world other than this one (the difference modality),
+/// - lowercase letters such as `i` and `j` as nominals, each true at exactly the world it names, and
+/// - `@i φ`: φ holds at the world named by `i`, and
+/// - `X φ` and `Y φ`: φ holds at the next / previous world (see `Connective::Next`).
+///
+/// `A`, `E`, `D`, `@i`, `X` and `Y` are prefix operators like `¬`, `◇` and `□`. A nominal that names no world of
+/// the model is false everywhere, and so is `@i φ` for it.
+pub fn parse_enriched_formula(source: &str) -> Result<Proposition> {
+    try_parse_proposition(source, &HashMap::new(), true, 0)
+}
+
+/// Whether `source` parses, in the enriched language, to a tree that survives printing: the printed
+/// formula parses back to the same tree up to parentheses, and printing that gives the same text again.
+/// A guard against ambiguities creeping into the grammar; text that doesn't parse is not unambiguous.
+pub fn is_unambiguous_parse(source: &str) -> bool {
+    let parsed = match parse_enriched_formula(source) {
+        Ok(parsed) => parsed,
+        Err(_) => return false,
+    };
+    let printed = parsed.to_string();
+    match parse_enriched_formula(&printed) {
+        Ok(reparsed) => without_parentheses(&reparsed) == without_parentheses(&parsed) && reparsed.to_string() == printed,
+        Err(_) => false,
+    }
+}
+
+fn without_parentheses(prop: &Proposition) -> Proposition {
+    match prop {
+        Proposition::Parenthesised(inner) => without_parentheses(inner),
+        _ => prop.with_operands(prop.operands().into_iter().map(without_parentheses).collect()),
+    }
 }
 
 fn parse_proposition_string(prop: &str) -> Proposition {
-    let mut prop = prop.to_string();
-    prop.retain(|c| !c.is_whitespace());
-    let mut prop = prop.split(';');
-    let proposition = prop.next().unwrap();
-    let mut atoms = prop.next().unwrap().split(',');
+    try_parse_proposition_string(prop).unwrap_or_else(|error| panic!("{}", error))
+}
+
+// An assignment's value is either a truth literal or a parenthesised formula abbreviated by the atom,
+// as in `P;P=(Q∧R),Q=T,R=F`. Abbreviations are substituted into the formula, and may use each other.
+fn try_parse_proposition_string(prop: &str) -> Result<Proposition> {
+    let (proposition, assignments) = match prop.split_once(';') {
+        Some(parts) => parts,
+        None => return parse_error(prop.len(), "expected `;` followed by atom assignments"),
+    };
+    let (atom_map, definitions) = parse_assignments(assignments, proposition.len() + 1)?;
+    let proposition = try_parse_proposition(proposition, &atom_map, false, 0)?;
+    expand_definitions(&proposition, &definitions, &mut Vec::new())
+}
+
+/// Evaluates several formulas under one shared assignment block, as in `"P ∧ Q\nP ∨ Q\n;P=T,Q=F"`.
+///
+/// Everything before the first `;` is the formulas, one per line; a line ending in `\` continues onto
+/// the next, blank lines are skipped and there must be at least one formula. Everything after it is a
+/// single assignment block in the syntax of `formula;assignment` strings, applied to every formula.
+/// Results are in the order the formulas appear.
+pub fn evaluate_propositional_sequence(source: &str) -> Result<Vec<bool>> {
+    try_parse_proposition_sequence(source)?.iter().map(|prop| eval_rpn(&to_rpn(prop), &HashMap::new())).collect()
+}
+
+fn try_parse_proposition_sequence(source: &str) -> Result<Vec<Proposition>> {
+    let (formulas, assignments) = match source.split_once(';') {
+        Some(parts) => parts,
+        None => return parse_error(source.len(), "expected `;` followed by atom assignments"),
+    };
+    let (atom_map, definitions) = parse_assignments(assignments, formulas.len() + 1)?;
+    let mut props = Vec::new();
+    let mut offset = 0;
+    // The formula being read and where it starts. A continuation `\` becomes a space, keeping positions.
+    let mut pending: Option<(String, usize)> = None;
+    let lines: Vec<&str> = formulas.split('\n').collect();
+    for (index, line) in lines.iter().enumerate() {
+        let (text, _) = pending.get_or_insert_with(|| (String::new(), offset));
+        let continued = line.trim_end().strip_suffix('\\');
+        match continued {
+            Some(head) => {
+                text.push_str(head);
+                text.push(' ');
+                text.push_str(&line[head.len() + 1..]);
+                text.push('\n');
+            }
+            None => text.push_str(line),
+        }
+        offset += line.len() + 1;
+        if continued.is_some() && index + 1 < lines.len() {
+            continue;
+        }
+        let (text, start) = pending.take().expect("a formula is being read");
+        if !text.trim().is_empty() {
+            let prop = try_parse_proposition(&text, &atom_map, false, start)?;
+            props.push(expand_definitions(&prop, &definitions, &mut Vec::new())?);
+        }
+    }
+    if props.is_empty() {
+        return parse_error(0, "expected at least one formula before `;`");
+    }
+    Ok(props)
+}
+
+type Definitions = HashMap<char, (Proposition, usize)>;
+
+// Parses the assignments after a `;`, which start at `offset` in the source, into the atoms given truth
+// values and the atoms abbreviating formulas.
+fn parse_assignments(assignments: &str, mut offset: usize) -> Result<(HashMap<char, char>, Definitions)> {
     let mut atom_map: HashMap<char, char> = HashMap::new();
-    for atom in atoms {
-        let mut atom = atom.split('=');
-        let atom_name = atom.next().unwrap().chars().next().unwrap();
-        let atom_value = atom.next().unwrap().chars().next().unwrap();
-        atom_map.insert(atom_name, atom_value);
-    }
-    parse_proposition(proposition, &atom_map)
-}
-
-fn parse_proposition(prop: &str, atom_map: &HashMap<char, char>) -> Proposition {
-    let mut prop = prop.to_string();
-    prop.retain(|c| !c.is_whitespace());
-    let mut prop = prop.chars();
-    let mut current_char = prop.next();
-    let mut current_prop = None;
-    while let Some(c) = current_char {
-        match c {
-            'P' | 'Q' | 'R' | 'S' | 'T' => {
-                let atom = atom_map.get(&c as &char).unwrap();
-                let atom = match atom {
-                    'T' => Atom::True,
-                    'F' => Atom::False,
-                    _ => panic!("Invalid atom value"),
-                };
-                current_prop = Some(Proposition::Atom(atom));
+    let mut definitions: HashMap<char, (&str, usize)> = HashMap::new();
+    for atom in assignments.split(',') {
+        let (atom_name, atom_value) = match atom.split_once('=') {
+            Some(parts) => parts,
+            None => return parse_error(offset, "expected an assignment like `P=T`"),
+        };
+        let value_offset = offset + atom_name.len() + 1;
+        let atom_name = match single_char(atom_name) {
+            Some(c) => c,
+            None => return parse_error(offset, "expected a single atom name"),
+        };
+        if atom_value.trim_start().starts_with('(') {
+            atom_map.remove(&atom_name);
+            definitions.insert(atom_name, (atom_value, value_offset));
+        } else {
+            match single_char(atom_value) {
+                Some(c @ ('T' | 'F')) => atom_map.insert(atom_name, c),
+                _ => return parse_error(value_offset, "invalid atom value, expected `T`, `F` or a parenthesised formula"),
+            };
+            definitions.remove(&atom_name);
+        }
+        offset += atom.len() + 1;
+    }
+    let mut parsed_definitions = HashMap::new();
+    for (name, (source, position)) in definitions {
+        parsed_definitions.insert(name, (try_parse_proposition(source, &atom_map, false, position)?, position));
+    }
+    Ok((atom_map, parsed_definitions))
+}
+
+fn expand_definitions(prop: &Proposition, definitions: &Definitions, expanding: &mut Vec<char>) -> Result<Proposition> {
+    if let Proposition::Atom(Atom::Variable(name)) = prop {
+        if let Some((definition, position)) = definitions.get(name) {
+            if expanding.contains(name) {
+                return parse_error(*position, &format!("cyclic definition of {}", name));
             }
-            '∧' => {
-                let left = current_prop.unwrap();
-                let right = parse_proposition(&prop.collect::<String>(), atom_map);
-                current_prop = Some(Proposition::Connective(Connective::And(Box::new(left), Box::new(right))));
-                break;
+            expanding.push(*name);
+            let expanded = expand_definitions(definition, definitions, expanding)?;
+            expanding.pop();
+            return Ok(expanded);
+        }
+    }
+    let operands = prop.operands().into_iter().map(|operand| expand_definitions(operand, definitions, expanding)).collect::<Result<Vec<_>>>()?;
+    Ok(prop.with_operands(operands))
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars().filter(|c| !c.is_whitespace());
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn parse_error<T>(position: usize, message: &str) -> Result<T> {
+    Err(ParseError::new(position, message).into())
+}
+
+// `offset` is where `prop` starts within the source being parsed, so errors point into the source.
+// `enriched` turns on the operators and nominals of `parse_enriched_formula`.
+fn try_parse_proposition(prop: &str, atom_map: &HashMap<char, char>, enriched: bool, offset: usize) -> Result<Proposition> {
+    let mut parser = Parser { chars: prop.char_indices().peekable(), end: offset + prop.len(), offset, atom_map, enriched };
+    let proposition = parser.formula()?;
+    match parser.peek() {
+        Some((position, _)) => parse_error(position, "unmatched closing parenthesis"),
+        None => Ok(proposition),
+    }
+}
+
+// The binary connectives from loosest to tightest.
+const BINARY: [char; 4] = ['↔', '→', '∨', '∧'];
+
+fn binary_connective(connective: char, left: Proposition, right: Proposition) -> Proposition {
+    match connective {
+        '∧' => Proposition::and(left, right),
+        '∨' => Proposition::or(left, right),
+        '→' => Proposition::if_then(left, right),
+        _ => Proposition::iff(left, right),
+    }
+}
+
+// A recursive-descent parser. Prefix operators bind tighter than any binary connective.
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    // Positions are offset so they point into the whole source, of which this text may be a part.
+    offset: usize,
+    end: usize,
+    atom_map: &'a HashMap<char, char>,
+    enriched: bool,
+}
+
+impl Parser<'_> {
+    // The next character other than whitespace, and its position.
+    fn peek(&mut self) -> Option<(usize, char)> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        self.chars.peek().map(|(index, c)| (self.offset + index, *c))
+    }
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        let next = self.peek();
+        self.chars.next();
+        next
+    }
+
+    fn formula(&mut self) -> Result<Proposition> {
+        self.binary(0)
+    }
+
+    // The connectives of `BINARY` from `level` on, loosest first. `∧` and `∨` group to the left and `→`
+    // and `↔` to the right, so `P → Q → R` is `P → (Q → R)`. Stops before a `)` closing an enclosing group.
+    fn binary(&mut self, level: usize) -> Result<Proposition> {
+        let connective = match BINARY.get(level) {
+            Some(connective) => *connective,
+            None => return self.operand(),
+        };
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let counterfactual = connective == '→' && self.at_counterfactual();
+            if !counterfactual && self.peek().is_none_or(|(_, c)| c != connective) {
+                return Ok(left);
+            }
+            self.next();
+            if counterfactual {
+                self.next();
+                return Ok(Proposition::counterfactual(left, self.binary(level)?));
             }
-            '∨' => {
-                let left = current_prop.unwrap();
-                let right = parse_proposition(&prop.collect::<String>(), atom_map);
-                current_prop = Some(Proposition::Connective(Connective::Or(Box::new(left), Box::new(right))));
-                break;
+            if matches!(connective, '→' | '↔') {
+                return Ok(binary_connective(connective, left, self.binary(level)?));
             }
-            '→' => {
-                let left = current_prop.unwrap();
-                let right = parse_proposition(&prop.collect::<String>(), atom_map);
-                current_prop = Some(Proposition::Connective(Connective::IfThen(Box::new(left), Box::new(right))));
-                break;
+            left = binary_connective(connective, left, self.binary(level + 1)?);
+        }
+    }
+
+    // Whether `□→` comes next. A `□` right after an operand can only start one.
+    fn at_counterfactual(&mut self) -> bool {
+        if self.peek().is_none_or(|(_, c)| c != '□') {
+            return false;
+        }
+        let mut ahead = self.chars.clone();
+        ahead.next();
+        ahead.peek().is_some_and(|(_, c)| *c == '→')
+    }
+
+    // A prefix formula, which must be followed by a binary connective, a `)` or the end of the text.
+    fn operand(&mut self) -> Result<Proposition> {
+        let operand = self.unary()?;
+        match self.peek() {
+            None | Some((_, ')')) => Ok(operand),
+            Some((_, c)) if BINARY.contains(&c) => Ok(operand),
+            Some(_) if self.at_counterfactual() => Ok(operand),
+            Some((position, c)) if self.starts_unary(c) => parse_error(position, "expected a connective"),
+            Some((position, c)) => parse_error(position, &format!("invalid character `{}`", c)),
+        }
+    }
+
+    fn starts_unary(&self, c: char) -> bool {
+        match c {
+            'P' | 'Q' | 'R' | 'S' | 'T' | '¬' | '◇' | '□' | 'K' | 'C' | '(' => true,
+            'a'..='z' | '@' | 'A' | 'E' | 'D' | 'X' | 'Y' => self.enriched,
+            _ => false,
+        }
+    }
+
+    // An atom, a nominal, a parenthesised formula, or a prefix operator applied to one of those.
+    fn unary(&mut self) -> Result<Proposition> {
+        let (position, c) = match self.next() {
+            Some(next) => next,
+            None => return parse_error(self.end, "expected a proposition"),
+        };
+        match c {
+            'P' | 'Q' | 'R' | 'S' | 'T' => {
+                let atom = match self.atom_map.get(&c) {
+                    Some('T') => Atom::True,
+                    Some('F') => Atom::False,
+                    Some(_) => return parse_error(position, "invalid atom value"),
+                    None => Atom::Variable(c),
+                };
+                Ok(Proposition::Atom(atom))
             }
-            '↔' => {
-                let left = current_prop.unwrap();
-                let right = parse_proposition(&prop.collect::<String>(), atom_map);
-                current_prop = Some(Proposition::Connective(Connective::Iff(Box::new(left), Box::new(right))));
-                break;
+            '∧' | '∨' | '→' | '↔' => parse_error(position, "expected a proposition before the connective"),
+            'a'..='z' if self.enriched => Ok(Proposition::nominal(c)),
+            '@' if self.enriched => match self.next() {
+                Some((_, nominal @ 'a'..='z')) => Ok(Proposition::at(nominal, self.unary()?)),
+                _ => parse_error(position + 1, "expected a nominal after `@`"),
+            },
+            'K' => match (self.chars.next_if(|(_, next)| *next == '_'), self.chars.next_if(|(_, next)| next.is_ascii_lowercase())) {
+                (Some(_), Some((_, agent))) => Ok(Proposition::knows(agent, self.unary()?)),
+                _ => parse_error(position + 1, "expected `_` and an agent after `K`"),
+            },
+            'C' => {
+                if self.chars.next_if(|(_, next)| *next == '_').is_none() || self.chars.next_if(|(_, next)| *next == '{').is_none() {
+                    return parse_error(position + 1, "expected `_{` and a group of agents after `C`");
+                }
+                let mut agents = Vec::new();
+                while let Some((_, agent)) = self.chars.next_if(|(_, next)| next.is_ascii_lowercase()) {
+                    agents.push(agent);
+                }
+                match self.chars.next() {
+                    Some((_, '}')) => Ok(Proposition::common_knowledge(Group::new(&agents), self.unary()?)),
+                    Some((index, _)) => parse_error(self.offset + index, "expected an agent or `}`"),
+                    None => parse_error(self.end, "expected an agent or `}`"),
+                }
             }
-            '¬' => {
-                let left = parse_proposition(&prop.collect::<String>(), atom_map);
-                current_prop = Some(Proposition::Connective(Connective::Not(Box::new(left))));
-                break;
+            '¬' | '◇' | '□' | 'A' | 'E' | 'D' | 'X' | 'Y' if self.enriched || !matches!(c, 'A' | 'E' | 'D' | 'X' | 'Y') => {
+                // `□*` and `◇*` also take in the current world.
+                let reflexive = matches!(c, '◇' | '□') && self.chars.next_if(|(_, next)| *next == '*').is_some();
+                let operand = self.unary()?;
+                Ok(match c {
+                    '¬' => Proposition::negation(operand),
+                    '◇' if reflexive => Proposition::or(operand.clone(), Proposition::possibly(operand)),
+                    '◇' => Proposition::possibly(operand),
+                    '□' if reflexive => Proposition::and(operand.clone(), Proposition::necessarily(operand)),
+                    '□' => Proposition::necessarily(operand),
+                    'A' => Proposition::everywhere(operand),
+                    'E' => Proposition::somewhere(operand),
+                    'X' => Proposition::next(operand),
+                    'Y' => Proposition::previous(operand),
+                    _ => Proposition::elsewhere(operand),
+                })
             }
             '(' => {
-                let mut paren_count = 1;
-                let mut paren_prop = String::new();
-                while let Some(c) = prop.next() {
-                    match c {
-                        '(' => paren_count += 1,
-                        ')' => paren_count -= 1,
-                        _ => (),
-                    }
-                    if paren_count == 0 {
-                        break;
-                    }
-                    paren_prop.push(c);
+                let inner = self.formula()?;
+                match self.next() {
+                    Some((_, ')')) => Ok(Proposition::Parenthesised(Box::new(inner))),
+                    _ => parse_error(position, "unclosed parenthesis"),
                 }
-                current_prop = Some(Proposition::Parenthesised(Box::new(parse_proposition(&paren_prop, atom_map))));
             }
-            _ => {
-                print!("{} ", c);
-                panic!("Invalid character");
-            },
+            ')' => parse_error(position, "expected a proposition"),
+            _ => parse_error(position, &format!("invalid character `{}`", c)),
         }
-        current_char = prop.next();
     }
-    current_prop.unwrap()
 }
 
 #[cfg(test)]
@@ -198,13 +814,171 @@ mod tests {
         assert_eq!(evaluate(also_parsed), false);
     }
 
+    #[test]
+    fn parses_formula_valued_assignments() {
+        assert!(!evaluate_propositional_string("P;P=(Q∧R),Q=T,R=F"));
+        assert!(evaluate_propositional_string("P ∧ S;P=(Q ∨ R),S=(¬R),Q=T,R=F"));
+        assert!(evaluate_propositional_string("P;P=(Q → R),R=(S),Q=T,S=T"));
+    }
+
+    #[test]
+    fn rejects_cyclic_definitions() {
+        let cyclic = try_parse_proposition_string("P;P=(Q ∧ R),Q=(¬P),R=T");
+        assert_eq!(cyclic, Err(Error::Parse(ParseError::new(4, "cyclic definition of P"))));
+        assert!(try_parse_proposition_string("Q;Q=(Q)").is_err());
+    }
+
+    #[test]
+    fn negation_binds_tighter_than_binary_connectives() {
+        // (¬P) ∧ Q and ¬(P ∧ Q) disagree whenever Q is false.
+        assert!(!evaluate_propositional_string("¬P ∧ Q;P=T,Q=F"));
+        assert!(!evaluate_propositional_string("¬P ∧ Q;P=F,Q=F"));
+        assert!(evaluate_propositional_string("¬P ∧ Q;P=F,Q=T"));
+        assert!(evaluate_propositional_string("¬(P ∧ Q);P=T,Q=F"));
+        assert_eq!(parse_formula("¬¬P ∨ Q").unwrap(), Proposition::or(Proposition::negation(Proposition::negation(Proposition::variable('P'))), Proposition::variable('Q')));
+    }
+
+    #[test]
+    fn negated_groups_leave_trailing_connectives_alone() {
+        let group = Proposition::Parenthesised(Box::new(Proposition::or(Proposition::variable('P'), Proposition::variable('Q'))));
+        assert_eq!(parse_formula("¬(P ∨ Q) ∧ R").unwrap(), Proposition::and(Proposition::negation(group), Proposition::variable('R')));
+        // Each assignment tells `(¬(…)) ∘ R` apart from `¬((…) ∘ R)`.
+        assert!(!evaluate_propositional_string("¬(P ∨ Q) ∧ R;P=F,Q=F,R=F"));
+        assert!(evaluate_propositional_string("¬(P ∧ Q) ∨ R;P=T,Q=T,R=T"));
+        assert!(evaluate_propositional_string("¬(P → Q) → R;P=F,Q=F,R=T"));
+        assert!(!evaluate_propositional_string("¬(P) ∧ ¬(Q ∨ R) ∨ S;P=T,Q=F,R=F,S=F"));
+        assert_eq!(parse_formula("¬(P ∨ Q) ∧ R ∨ ¬(S)").unwrap().to_string(), "(¬(P ∨ Q) ∧ R) ∨ ¬(S)");
+    }
+
+    #[test]
+    fn binary_connectives_follow_textbook_precedence() {
+        let parsed = |source| parse_formula(source).unwrap().to_string();
+        assert_eq!(parsed("P ∧ Q ∨ R"), "(P ∧ Q) ∨ R");
+        assert_eq!(parsed("P ∨ Q ∧ R"), "P ∨ (Q ∧ R)");
+        assert_eq!(parsed("P → Q ∧ R"), "P → (Q ∧ R)");
+        assert_eq!(parsed("P ∧ Q → R ∨ S ↔ T"), "((P ∧ Q) → (R ∨ S)) ↔ T");
+        assert_eq!(parsed("P → Q → R"), "P → (Q → R)");
+        assert_eq!(parsed("P ∧ Q ∧ R"), "(P ∧ Q) ∧ R");
+        assert_eq!(parsed("¬P ∨ (Q → R) ∧ S"), "¬P ∨ ((Q → R) ∧ S)");
+        // (P ∧ Q) ∨ R and P ∧ (Q ∨ R) disagree when P is false and R true.
+        assert!(evaluate_propositional_string("P ∧ Q ∨ R;P=F,Q=F,R=T"));
+        assert!(!evaluate_propositional_string("P → Q ∧ R;P=T,Q=T,R=F"));
+    }
+
+    #[test]
+    fn formulas_can_span_lines() {
+        let spread = "(P ∧ Q)\n  → (R\n    ∨ ◇S)";
+        assert_eq!(parse_formula(spread), parse_formula("(P ∧ Q) → (R ∨ ◇S)"));
+        // In a sequence, where newlines separate formulas, a trailing `\` continues a formula.
+        let sequence = "P ∧ \\\n  Q ∨ \\ \n  R\nP\n;P=F,Q=T,R=F";
+        assert_eq!(evaluate_propositional_sequence(sequence), Ok(vec![false, false]));
+        assert_eq!(
+            evaluate_propositional_sequence("P ∧ \\\nQ ∨\n;P=T,Q=T"),
+            Err(Error::Parse(ParseError::new(13, "expected a proposition")))
+        );
+    }
+
+    #[test]
+    fn printing_and_reparsing_is_a_fixpoint() {
+        let formulas = [
+            "P",
+            "¬¬P",
+            "P ∧ Q ∨ R → S ↔ T",
+            "P → Q → R",
+            "(P → Q) → R",
+            "P ∧ Q ∧ R",
+            "P ∧ (Q ∧ R)",
+            "P ↔ Q ↔ R",
+            "□◇¬(P ∨ Q)",
+            "□*P → ◇*Q",
+            "(□P) ∧ □(P → Q) → □Q",
+            "P □→ Q □→ R",
+            "(P □→ Q) → R",
+            "A (i → ◇j) ∧ E ¬P",
+            "@i D (P ∧ j)",
+            "¬@i ¬A P",
+        ];
+        for formula in formulas {
+            assert!(is_unambiguous_parse(formula), "{}", formula);
+        }
+        assert!(!is_unambiguous_parse("P ∧"));
+        assert!(!is_unambiguous_parse("(P"));
+    }
+
+    #[test]
+    fn parses_counterfactuals_at_the_level_of_conditionals() {
+        let parsed = |source: &str| parse_formula(source).unwrap().to_string();
+        assert_eq!(parsed("P ∧ Q □→ R"), "(P ∧ Q) □→ R");
+        assert_eq!(parsed("P □→ Q → R"), "P □→ (Q → R)");
+        assert_eq!(parsed("P □→ □Q"), "P □→ □Q");
+        assert_eq!(parse_formula("P □→ Q").unwrap(), Proposition::counterfactual(Proposition::variable('P'), Proposition::variable('Q')));
+        // In a plain model, the counterfactual is the strict conditional.
+        let mut model = Model::new();
+        model.add_world(1, &['P']);
+        model.add_edge(0, 1);
+        assert!(!evaluate_at(&model, 0, &parse_formula("P □→ Q").unwrap()));
+        assert!(evaluate_at(&model, 1, &parse_formula("P □→ Q").unwrap()));
+    }
+
+    #[test]
+    fn modal_operators_bind_like_negation() {
+        let t_axiom = parse_formula("□P → P").unwrap();
+        assert_eq!(t_axiom, Proposition::if_then(Proposition::necessarily(Proposition::variable('P')), Proposition::variable('P')));
+        let mut model = Model::new();
+        model.add_edge(0, 1);
+        model.add_world(1, &['P']);
+        assert!(!evaluate_at(&model, 0, &t_axiom));
+        assert!(evaluate_at(&model, 0, &parse_formula("□(P → P)").unwrap()));
+        assert_eq!(parse_formula("◇¬P ∧ Q").unwrap().to_string(), "◇¬P ∧ Q");
+        assert_eq!(parse_formula("P ∧ ¬"), Err(Error::Parse(ParseError::new(8, "expected a proposition"))));
+        assert_eq!(parse_formula("(P))"), Err(Error::Parse(ParseError::new(3, "unmatched closing parenthesis"))));
+    }
+
+    #[test]
+    fn desugars_reflexive_modalities() {
+        assert_eq!(parse_formula("□*P").unwrap(), Proposition::and(Proposition::variable('P'), Proposition::necessarily(Proposition::variable('P'))));
+        assert_eq!(parse_formula("◇* (Q)").unwrap().to_string(), "(Q) ∨ ◇(Q)");
+        let mut model = Model::new();
+        model.add_world(1, &['P']);
+        model.add_edge(0, 1);
+        assert!(evaluate_at(&model, 0, &parse_formula("□P").unwrap()));
+        assert!(!evaluate_at(&model, 0, &parse_formula("□*P").unwrap()));
+        assert!(evaluate_at(&model, 0, &parse_formula("◇*P").unwrap()));
+        assert!(parse_formula("□ *P").is_err());
+    }
+
+    #[test]
+    fn parses_enriched_operators_only_when_enabled() {
+        assert!(parse_formula("A P").is_err());
+        assert!(parse_formula("@i P").is_err());
+        assert_eq!(parse_enriched_formula("E ◇i"), Ok(Proposition::somewhere(Proposition::possibly(Proposition::nominal('i')))));
+        assert_eq!(parse_enriched_formula("@ P"), Err(Error::Parse(ParseError::new(1, "expected a nominal after `@`"))));
+    }
+
+    #[test]
+    fn evaluates_formulas_under_a_shared_assignment() {
+        assert_eq!(evaluate_propositional_sequence("P ∧ Q\nP ∨ Q\n;P=T,Q=F"), Ok(vec![false, true]));
+        assert_eq!(evaluate_propositional_sequence("\n¬R\n\nR → (P ∧ Q);P=T,Q=T,R=(P∧¬Q)"), Ok(vec![true, true]));
+        let error = evaluate_propositional_sequence("P\nP ∧ ∨ Q;P=T,Q=F").unwrap_err();
+        assert_eq!(error, Error::Parse(ParseError::new(8, "expected a proposition before the connective")));
+        assert!(evaluate_propositional_sequence("\n;P=T").is_err());
+    }
+
+    #[test]
+    fn displays_propositions() {
+        let prop = parse_formula("(P ∧ ¬Q) ∨ □(Q → R)").unwrap();
+        assert_eq!(prop.to_string(), "(P ∧ ¬Q) ∨ □(Q → R)");
+        let prop = Proposition::and(Proposition::or(Proposition::variable('P'), Proposition::variable('Q')), Proposition::at('i', Proposition::constant(true)));
+        assert_eq!(prop.to_string(), "(P ∨ Q) ∧ @i ⊤");
+    }
+
     #[test]
     fn parses_propositions() {
         let mut atom_map: HashMap<char, char> = HashMap::new();
         atom_map.insert('P', 'T');
         atom_map.insert('Q', 'F');
         let proposition = "P∧Q";
-        let parsed = parse_proposition(proposition, &atom_map);
+        let parsed = try_parse_proposition(proposition, &atom_map, false, 0).unwrap();
         assert_eq!(evaluate(parsed), false);
     }
 }
\ No newline at end of file
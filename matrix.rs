@@ -0,0 +1,75 @@
+use crate::{Model, World};
+
+impl Model {
+    /// The accessibility relation as a boolean matrix over the worlds of `order`: row `i`, column `j` is
+    /// whether `order[i]` sees `order[j]`.
+    pub fn accessibility_matrix(&self, order: &[World]) -> Vec<Vec<bool>> {
+        order.iter().map(|from| order.iter().map(|to| self.successors(*from).any(|next| next == *to)).collect()).collect()
+    }
+
+    /// The model on the worlds of `order` with the accessibility relation `matrix`, laid out as in
+    /// `accessibility_matrix`, where `valuation[i]` is the atoms true at `order[i]`.
+    pub fn from_matrix(order: &[World], matrix: &[Vec<bool>], valuation: &[&[char]]) -> Model {
+        assert!(matrix.len() == order.len() && valuation.len() == order.len(), "expected a row and a valuation for each world");
+        let mut model = Model::new();
+        for (world, true_atoms) in order.iter().zip(valuation) {
+            model.add_world(*world, true_atoms);
+        }
+        for (from, row) in order.iter().zip(matrix) {
+            assert_eq!(row.len(), order.len(), "expected a column for each world");
+            for (to, _) in order.iter().zip(row).filter(|(_, related)| **related) {
+                model.add_edge(*from, *to);
+            }
+        }
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::System;
+
+    fn multiply(left: &[Vec<bool>], right: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        let size = left.len();
+        (0..size).map(|row| (0..size).map(|column| (0..size).any(|middle| left[row][middle] && right[middle][column])).collect()).collect()
+    }
+
+    fn union(left: &[Vec<bool>], right: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        left.iter().zip(right).map(|(left, right)| left.iter().zip(right).map(|(a, b)| *a || *b).collect()).collect()
+    }
+
+    #[test]
+    fn round_trips_through_matrices() {
+        let mut model = Model::new();
+        model.add_world(5, &['P']);
+        model.add_world(2, &['Q']);
+        model.add_edge(5, 2);
+        model.add_edge(2, 2);
+        model.add_world(7, &[]);
+        let order = [5, 2, 7];
+        let matrix = model.accessibility_matrix(&order);
+        assert_eq!(matrix, vec![vec![false, true, false], vec![false, true, false], vec![false, false, false]]);
+        assert_eq!(Model::from_matrix(&order, &matrix, &[&['P'], &['Q'], &[]]), model);
+    }
+
+    #[test]
+    fn matrix_powers_give_the_reflexive_transitive_closure() {
+        // A chain 0 → 1 → 2 → 3 with a shortcut back from 3 to 1.
+        let order = [0, 1, 2, 3];
+        let mut matrix = vec![vec![false; 4]; 4];
+        for (from, to) in [(0, 1), (1, 2), (2, 3), (3, 1)] {
+            matrix[from][to] = true;
+        }
+        let model = Model::from_matrix(&order, &matrix, &[&[] as &[char]; 4]);
+
+        let identity: Vec<Vec<bool>> = (0..4).map(|row| (0..4).map(|column| row == column).collect()).collect();
+        let (mut closure, mut power) = (identity.clone(), identity);
+        for _ in 0..order.len() {
+            power = multiply(&power, &matrix);
+            closure = union(&closure, &power);
+        }
+        assert_eq!(model.close_under(System::S4).accessibility_matrix(&order), closure);
+        assert!(!closure[1][0]);
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Atom, Connective, Group, Proposition};
+
+pub type World = usize;
+
+/// A Kripke model: worlds, an accessibility relation between them, and the atoms true at each world.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Model {
+    relation: BTreeMap<World, BTreeSet<World>>,
+    valuation: BTreeMap<World, BTreeSet<char>>,
+    names: BTreeMap<char, World>,
+}
+
+impl Model {
+    pub fn new() -> Model {
+        Model::default()
+    }
+
+    /// Adds `world` (if it isn't already present) and makes `true_atoms` true there.
+    pub fn add_world(&mut self, world: World, true_atoms: &[char]) {
+        self.valuation.entry(world).or_default().extend(true_atoms);
+        self.relation.entry(world).or_default();
+    }
+
+    /// Makes `to` accessible from `from`, adding either world if needed.
+    pub fn add_edge(&mut self, from: World, to: World) {
+        self.add_world(from, &[]);
+        self.add_world(to, &[]);
+        self.relation.entry(from).or_default().insert(to);
+    }
+
+    /// Makes the nominal `name` true at `world` alone, adding the world if needed.
+    pub fn name_world(&mut self, name: char, world: World) {
+        self.add_world(world, &[]);
+        self.names.insert(name, world);
+    }
+
+    pub fn named(&self, name: char) -> Option<World> {
+        self.names.get(&name).copied()
+    }
+
+    /// Each nominal with the world it names.
+    pub fn names(&self) -> impl Iterator<Item = (char, World)> + '_ {
+        self.names.iter().map(|(name, world)| (*name, *world))
+    }
+
+    pub fn remove_edge(&mut self, from: World, to: World) {
+        if let Some(successors) = self.relation.get_mut(&from) {
+            successors.remove(&to);
+        }
+    }
+
+    pub fn set_atom(&mut self, world: World, atom: char, value: bool) {
+        let atoms = self.valuation.entry(world).or_default();
+        if value {
+            atoms.insert(atom);
+        } else {
+            atoms.remove(&atom);
+        }
+        self.relation.entry(world).or_default();
+    }
+
+    pub fn worlds(&self) -> impl Iterator<Item = World> + '_ {
+        self.valuation.keys().copied()
+    }
+
+    pub fn successors(&self, world: World) -> impl Iterator<Item = World> + '_ {
+        self.relation.get(&world).into_iter().flatten().copied()
+    }
+
+    /// The worlds reachable from `world` in any number of steps, including `world` itself.
+    pub fn reachable_from(&self, world: World) -> BTreeSet<World> {
+        let mut reachable = BTreeSet::from([world]);
+        let mut frontier = vec![world];
+        while let Some(world) = frontier.pop() {
+            for next in self.successors(world) {
+                if reachable.insert(next) {
+                    frontier.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// How many distinct worlds are reachable from `world` in exactly `k` steps, for `k` from 0 up. A
+    /// formula of modal depth `d` at `world` only looks as far as the first `d + 1` entries. The profile
+    /// stops once no world is that many steps away, or once the worlds `k` steps away are the same as
+    /// for some smaller `k`, from which point on it would only cycle.
+    pub fn rank_profile(&self, world: World) -> Vec<usize> {
+        let mut seen: Vec<BTreeSet<World>> = Vec::new();
+        let mut layer = BTreeSet::from([world]);
+        while !layer.is_empty() && !seen.contains(&layer) {
+            let next = layer.iter().flat_map(|world| self.successors(*world)).collect();
+            seen.push(std::mem::replace(&mut layer, next));
+        }
+        seen.iter().map(BTreeSet::len).collect()
+    }
+
+    /// The submodel on `worlds`: their valuations, and the edges between them.
+    pub fn restrict(&self, worlds: &BTreeSet<World>) -> Model {
+        let mut submodel = Model::new();
+        for world in self.worlds().filter(|world| worlds.contains(world)) {
+            submodel.valuation.insert(world, self.valuation[&world].clone());
+            submodel.relation.insert(world, self.successors(world).filter(|next| worlds.contains(next)).collect());
+        }
+        submodel.names = self.names.iter().filter(|(_, world)| worlds.contains(world)).map(|(name, world)| (*name, *world)).collect();
+        submodel
+    }
+
+    /// The submodel generated by `world`: everything reachable from it.
+    pub fn generated_submodel(&self, world: World) -> Model {
+        self.restrict(&self.reachable_from(world))
+    }
+
+    /// Every atom true at some world of the model.
+    pub fn atoms(&self) -> BTreeSet<char> {
+        self.valuation.values().flatten().copied().collect()
+    }
+
+    pub fn holds(&self, world: World, atom: char) -> bool {
+        self.valuation.get(&world).is_some_and(|atoms| atoms.contains(&atom))
+    }
+}
+
+/// Evaluates `prop` at `world`: `◇φ` holds if φ holds at some accessible world, `□φ` if at all of them.
+/// With every accessible world equally close, `φ □→ ψ` is the strict conditional `□(φ → ψ)`. With a
+/// single relation every agent's knowledge `K_a φ` is `□φ`, and common knowledge `C_G φ` among a
+/// nonempty group is φ at every world reachable from this one, this one included. `X φ` and `Y φ` hold
+/// if φ holds at every successor or predecessor and there's at least one, which on a linear chain is
+/// the next or previous world; at the chain's last and first worlds they're false.
+pub fn evaluate_at(model: &Model, world: World, prop: &Proposition) -> bool {
+    evaluate_in(model, world, prop)
+}
+
+/// Switches for `evaluate_at_with` that change how a model is read without changing the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvaluationOptions {
+    /// Treat every world as accessible from itself, as if the model were closed under `System::T`.
+    pub assume_reflexive: bool,
+}
+
+/// Evaluates `prop` at `world` as `evaluate_at` does, adjusted by `options`.
+pub fn evaluate_at_with(model: &Model, world: World, prop: &Proposition, options: EvaluationOptions) -> bool {
+    evaluate_in(&Adjusted { model, options }, world, prop)
+}
+
+struct Adjusted<'a> {
+    model: &'a Model,
+    options: EvaluationOptions,
+}
+
+// A `Model` with whatever further structure decides the operators the accessibility relation alone
+// doesn't: the defaults read them off the relation, as `evaluate_at` does.
+pub(crate) trait Semantics {
+    fn model(&self) -> &Model;
+
+    fn successors(&self, world: World) -> impl Iterator<Item = World> + '_ {
+        self.model().successors(world)
+    }
+
+    fn counterfactual(&self, world: World, antecedent: &Proposition, consequent: &Proposition) -> bool {
+        self.successors(world).all(|next| !evaluate_in(self, next, antecedent) || evaluate_in(self, next, consequent))
+    }
+
+    fn knows(&self, _agent: char, world: World, prop: &Proposition) -> bool {
+        self.successors(world).all(|next| evaluate_in(self, next, prop))
+    }
+
+    // Every agent reads the accessibility relation, as for `knows`, so any agent at all reaches
+    // whatever it does.
+    fn common_knowledge(&self, group: Group, world: World, prop: &Proposition) -> bool {
+        let reachable = if group.is_empty() { BTreeSet::from([world]) } else { self.model().reachable_from(world) };
+        reachable.into_iter().all(|other| evaluate_in(self, other, prop))
+    }
+}
+
+impl Semantics for Model {
+    fn model(&self) -> &Model {
+        self
+    }
+}
+
+impl Semantics for Adjusted<'_> {
+    fn model(&self) -> &Model {
+        self.model
+    }
+
+    fn successors(&self, world: World) -> impl Iterator<Item = World> + '_ {
+        let own = (self.options.assume_reflexive && !self.model.successors(world).any(|next| next == world)).then_some(world);
+        self.model.successors(world).chain(own)
+    }
+}
+
+pub(crate) fn evaluate_in<S: Semantics + ?Sized>(semantics: &S, world: World, prop: &Proposition) -> bool {
+    let model = semantics.model();
+    let evaluate_at = |world: World, prop: &Proposition| evaluate_in(semantics, world, prop);
+    match prop {
+        Proposition::Atom(Atom::True) => true,
+        Proposition::Atom(Atom::False) => false,
+        Proposition::Atom(Atom::Variable(name)) => model.holds(world, *name),
+        Proposition::Atom(Atom::Nominal(name)) => model.named(*name) == Some(world),
+        Proposition::Connective(Connective::And(left, right)) => evaluate_at(world, left) && evaluate_at(world, right),
+        Proposition::Connective(Connective::Or(left, right)) => evaluate_at(world, left) || evaluate_at(world, right),
+        Proposition::Connective(Connective::IfThen(left, right)) => !evaluate_at(world, left) || evaluate_at(world, right),
+        Proposition::Connective(Connective::Iff(left, right)) => evaluate_at(world, left) == evaluate_at(world, right),
+        Proposition::Connective(Connective::Not(prop)) => !evaluate_at(world, prop),
+        Proposition::Connective(Connective::Possibly(prop)) => semantics.successors(world).any(|next| evaluate_at(next, prop)),
+        Proposition::Connective(Connective::Necessarily(prop)) => semantics.successors(world).all(|next| evaluate_at(next, prop)),
+        Proposition::Connective(Connective::Everywhere(prop)) => model.worlds().all(|world| evaluate_at(world, prop)),
+        Proposition::Connective(Connective::Somewhere(prop)) => model.worlds().any(|world| evaluate_at(world, prop)),
+        Proposition::Connective(Connective::At(name, prop)) => model.named(*name).is_some_and(|named| evaluate_at(named, prop)),
+        Proposition::Connective(Connective::Elsewhere(prop)) => model.worlds().any(|other| other != world && evaluate_at(other, prop)),
+        Proposition::Connective(Connective::Counterfactual(antecedent, consequent)) => semantics.counterfactual(world, antecedent, consequent),
+        Proposition::Connective(Connective::Knows(agent, prop)) => semantics.knows(*agent, world, prop),
+        Proposition::Connective(Connective::CommonKnowledge(group, prop)) => semantics.common_knowledge(*group, world, prop),
+        Proposition::Connective(Connective::Next(prop)) => {
+            let successors: Vec<World> = semantics.successors(world).collect();
+            !successors.is_empty() && successors.into_iter().all(|next| evaluate_at(next, prop))
+        }
+        Proposition::Connective(Connective::Previous(prop)) => {
+            let predecessors: Vec<World> = model.worlds().filter(|other| semantics.successors(*other).any(|next| next == world)).collect();
+            !predecessors.is_empty() && predecessors.into_iter().all(|previous| evaluate_at(previous, prop))
+        }
+        Proposition::Parenthesised(prop) => evaluate_at(world, prop),
+    }
+}
+
+/// How truth at a set of worlds is read off from truth at its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruthMode {
+    /// Super-truth: φ holds at every world in the set. The empty set makes everything super-true.
+    SuperTruth,
+    /// Sub-truth: φ holds at some world in the set. Nothing is sub-true at the empty set.
+    SubTruth,
+}
+
+/// Evaluates `prop` at a set of points of evaluation rather than a single world, e.g. the
+/// admissible precisifications of a supervaluationist model.
+pub fn evaluate_at_set(model: &Model, worlds: &BTreeSet<World>, prop: &Proposition, mode: TruthMode) -> bool {
+    match mode {
+        TruthMode::SuperTruth => worlds.iter().all(|world| evaluate_at(model, *world, prop)),
+        TruthMode::SubTruth => worlds.iter().any(|world| evaluate_at(model, *world, prop)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_enriched_formula, parse_formula};
+
+    fn chain() -> Model {
+        let mut model = Model::new();
+        model.add_world(0, &['P']);
+        model.add_world(1, &['P', 'Q']);
+        model.add_world(2, &['Q']);
+        model.add_edge(0, 1);
+        model.add_edge(1, 2);
+        model
+    }
+
+    #[test]
+    fn evaluates_modal_connectives_at_worlds() {
+        let model = chain();
+        let possibly_q = Proposition::possibly(Proposition::variable('Q'));
+        let necessarily_p = Proposition::necessarily(Proposition::variable('P'));
+        assert!(evaluate_at(&model, 0, &possibly_q));
+        assert!(!evaluate_at(&model, 2, &possibly_q));
+        assert!(evaluate_at(&model, 0, &necessarily_p));
+        assert!(!evaluate_at(&model, 1, &necessarily_p));
+        assert!(evaluate_at(&model, 2, &necessarily_p));
+    }
+
+    #[test]
+    fn profiles_the_worlds_at_each_distance() {
+        // A diamond 0 → {1, 2} → 3, with 3 → 4 → 3 looping.
+        let mut model = Model::new();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (4, 3)] {
+            model.add_edge(from, to);
+        }
+        assert_eq!(model.rank_profile(0), vec![1, 2, 1, 1]);
+        assert_eq!(model.rank_profile(3), vec![1, 1]);
+        assert_eq!(chain().rank_profile(0), vec![1, 1, 1]);
+        assert_eq!(chain().rank_profile(2), vec![1]);
+    }
+
+    #[test]
+    fn evaluates_next_and_previous_along_a_chain() {
+        let model = chain();
+        let at = |world, source| evaluate_at(&model, world, &parse_enriched_formula(source).unwrap());
+        assert!(at(0, "X (P ∧ Q)"));
+        assert!(!at(1, "X P"));
+        assert!(at(1, "X Q ∧ Y ¬Q"));
+        // The last world has no next world, and the first no previous one, whatever φ is.
+        assert!(!at(2, "X Q") && !at(2, "X ¬Q"));
+        assert!(!at(0, "Y P") && !at(0, "Y ¬P"));
+        assert!(at(2, "Y X Q"));
+        assert_eq!(parse_enriched_formula("X Y P").unwrap().to_string(), "X Y P");
+    }
+
+    #[test]
+    fn reads_common_knowledge_off_the_reachable_worlds() {
+        let model = chain();
+        let at = |world, source| evaluate_at(&model, world, &parse_formula(source).unwrap());
+        // Everyone knows P at 0, but at 1 they can't rule out 2, where P fails.
+        assert!(at(0, "K_a P ∧ K_b P") && !at(0, "C_{ab} P"));
+        assert!(at(0, "C_{ab} (P ∨ Q)"));
+        assert!(at(1, "C_{a} Q") && at(0, "K_a C_{ab} Q"));
+        assert!(at(0, "C_{} P"));
+        assert_eq!(parse_formula("K_a C_{ba} P").unwrap().to_string(), "K_a C_{ab} P");
+        assert!(parse_formula("C_a P").is_err() && parse_formula("C_{aB} P").is_err());
+    }
+
+    #[test]
+    fn can_assume_reflexivity_without_changing_the_model() {
+        let model = chain();
+        let t_axiom = parse_formula("□P → P").unwrap();
+        let reflexive = EvaluationOptions { assume_reflexive: true };
+        assert!(!evaluate_at(&model, 2, &t_axiom));
+        assert!(evaluate_at_with(&model, 2, &t_axiom, reflexive));
+        assert_eq!(evaluate_at_with(&model, 2, &t_axiom, EvaluationOptions::default()), evaluate_at(&model, 2, &t_axiom));
+        // 1 now sees itself as well as 2, which lacks P.
+        assert!(!evaluate_at_with(&model, 1, &Proposition::necessarily(Proposition::variable('P')), reflexive));
+        assert!(evaluate_at_with(&model, 2, &Proposition::possibly(Proposition::variable('Q')), reflexive));
+        assert_eq!(model.successors(2).count(), 0);
+    }
+
+    #[test]
+    fn evaluates_at_sets_of_worlds() {
+        let model = chain();
+        let p = Proposition::variable('P');
+        let settled: BTreeSet<World> = [0, 1].into();
+        let borderline: BTreeSet<World> = [1, 2].into();
+        assert!(evaluate_at_set(&model, &settled, &p, TruthMode::SuperTruth));
+        assert!(!evaluate_at_set(&model, &borderline, &p, TruthMode::SuperTruth));
+        assert!(evaluate_at_set(&model, &borderline, &p, TruthMode::SubTruth));
+        assert!(!evaluate_at_set(&model, &BTreeSet::new(), &p, TruthMode::SubTruth));
+    }
+
+    #[test]
+    fn evaluates_nominals_with_the_universal_modality() {
+        let mut model = chain();
+        model.name_world('i', 1);
+        let prop = parse_enriched_formula("@i A P").unwrap();
+        assert_eq!(prop, Proposition::at('i', Proposition::everywhere(Proposition::variable('P'))));
+        assert!(!evaluate_at(&model, 0, &prop));
+        model.set_atom(2, 'P', true);
+        assert!(evaluate_at(&model, 0, &prop));
+        assert!(!evaluate_at(&model, 0, &parse_enriched_formula("@j A P").unwrap()));
+    }
+
+    #[test]
+    fn evaluates_the_difference_modality() {
+        let mut model = chain();
+        model.name_world('i', 2);
+        // Q holds at 1 as well as at i, and `D i` holds everywhere except at i itself.
+        assert!(evaluate_at(&model, 0, &parse_enriched_formula("@i D Q").unwrap()));
+        assert!(!evaluate_at(&model, 2, &parse_enriched_formula("D i").unwrap()));
+        assert!(evaluate_at(&model, 0, &parse_enriched_formula("D i").unwrap()));
+        assert!(evaluate_at(&model, 0, &parse_enriched_formula("E (i ∧ ¬P)").unwrap()));
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::BTreeSet;
+
+/// The order atoms are laid out in by order-sensitive outputs such as truth table columns and BDD levels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AtomOrder {
+    #[default]
+    Sorted,
+    /// The listed atoms first, in the order given, then any others in sorted order. Listed atoms that
+    /// don't occur are skipped.
+    Custom(Vec<char>),
+}
+
+impl AtomOrder {
+    pub fn arrange(&self, atoms: &BTreeSet<char>) -> Vec<char> {
+        match self {
+            AtomOrder::Sorted => atoms.iter().copied().collect(),
+            AtomOrder::Custom(order) => {
+                let mut arranged: Vec<char> = Vec::with_capacity(atoms.len());
+                for atom in order.iter().chain(atoms) {
+                    if atoms.contains(atom) && !arranged.contains(atom) {
+                        arranged.push(*atom);
+                    }
+                }
+                arranged
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puts_listed_atoms_first() {
+        let atoms = BTreeSet::from(['P', 'Q', 'R', 'S']);
+        assert_eq!(AtomOrder::Sorted.arrange(&atoms), vec!['P', 'Q', 'R', 'S']);
+        assert_eq!(AtomOrder::Custom(vec!['R', 'T', 'P', 'R']).arrange(&atoms), vec!['R', 'P', 'Q', 'S']);
+    }
+}
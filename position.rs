@@ -0,0 +1,67 @@
+use crate::Proposition;
+
+/// Every subformula of `prop` in pre-order, each with its path from the root: the index of the operand
+/// taken at each step, left to right, so the root is at `[]` and the right operand of a binary root at
+/// `[1]`. A parenthesised group is a node of its own, with its contents at index 0.
+pub fn positions(prop: &Proposition) -> impl Iterator<Item = (Vec<usize>, &Proposition)> {
+    let mut pending = vec![(Vec::new(), prop)];
+    std::iter::from_fn(move || {
+        let (path, prop) = pending.pop()?;
+        for (index, operand) in prop.operands().into_iter().enumerate().rev() {
+            pending.push(([path.as_slice(), &[index]].concat(), operand));
+        }
+        Some((path, prop))
+    })
+}
+
+impl Proposition {
+    /// The subformula at `path`, as `positions` numbers them, or `None` if there's no such position.
+    pub fn get_at(&self, path: &[usize]) -> Option<&Proposition> {
+        match path.split_first() {
+            None => Some(self),
+            Some((index, rest)) => self.operands().get(*index)?.get_at(rest),
+        }
+    }
+
+    /// This formula with the subformula at `path` replaced by `new`, or `None` if there's no such position.
+    pub fn replace_at(&self, path: &[usize], new: Proposition) -> Option<Proposition> {
+        let (index, rest) = match path.split_first() {
+            None => return Some(new),
+            Some(split) => split,
+        };
+        let mut operands: Vec<Proposition> = self.operands().into_iter().cloned().collect();
+        let replaced = operands.get(*index)?.replace_at(rest, new)?;
+        operands[*index] = replaced;
+        Some(self.with_operands(operands))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    #[test]
+    fn lists_subformulas_with_their_paths() {
+        let prop = parse_formula("P → ¬(Q ∧ R)").unwrap();
+        let listed: Vec<(Vec<usize>, String)> = positions(&prop).map(|(path, sub)| (path, sub.to_string())).collect();
+        assert_eq!(listed[0], (vec![], "P → ¬(Q ∧ R)".to_string()));
+        assert_eq!(listed[1], (vec![0], "P".to_string()));
+        assert_eq!(listed[2], (vec![1], "¬(Q ∧ R)".to_string()));
+        assert_eq!(listed.last(), Some(&(vec![1, 0, 0, 1], "R".to_string())));
+        assert_eq!(listed.len(), prop.size() + 1);
+        for (path, sub) in positions(&prop) {
+            assert_eq!(prop.get_at(&path), Some(sub));
+        }
+    }
+
+    #[test]
+    fn replaces_the_subformula_at_a_path() {
+        let prop = parse_formula("P → ◇(Q ∧ R)").unwrap();
+        let replaced = prop.replace_at(&[1, 0, 0, 0], parse_formula("□S").unwrap()).unwrap();
+        assert_eq!(replaced.to_string(), "P → ◇(□S ∧ R)");
+        assert_eq!(prop.replace_at(&[], Proposition::variable('S')), Some(Proposition::variable('S')));
+        assert_eq!(prop.replace_at(&[0, 0], Proposition::variable('S')), None);
+        assert_eq!(prop.get_at(&[2]), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeSet;
+
+use crate::{from_cnf, Clause, Literal, Proposition, TruthTable};
+
+// An implicant over a table's atoms as the row bits it fixes (`care`) and their `values`, with the
+// first atom as the most significant bit, as in `TruthTable` rows.
+type Cube = (u64, u64);
+
+/// The prime implicants of `prop`: the conjunctions of literals that entail it and stop doing so if any
+/// literal is dropped, found by Quine–McCluskey over its truth table. Each is sorted, and they're listed
+/// shortest first. A tautology has the single empty implicant; a contradiction has none.
+pub fn prime_implicants(prop: &Proposition) -> Vec<Vec<Literal>> {
+    let table = TruthTable::new(prop);
+    let minterms = (0..).zip(table.rows()).filter(|(_, row)| row.result).map(|(index, _)| index);
+    to_literals(table.atoms(), &primes(table.atoms().len(), minterms), true)
+}
+
+/// The prime implicates of `prop`: the clauses it entails that stop being entailed if any literal is
+/// dropped, found as the negations of the prime implicants of `¬prop`. Each is sorted, and they're listed
+/// shortest first; their conjunction is equivalent to `prop`. A contradiction has the single empty clause.
+pub fn prime_implicates(prop: &Proposition) -> Vec<Clause> {
+    let table = TruthTable::new(prop);
+    let maxterms = (0..).zip(table.rows()).filter(|(_, row)| !row.result).map(|(index, _)| index);
+    to_literals(table.atoms(), &primes(table.atoms().len(), maxterms), false)
+}
+
+/// A smallest CNF equivalent to `prop`: as few prime implicates as cover its falsifying assignments, the
+/// fewest literals among those, chosen by exhaustive branch-and-bound search.
+pub fn minimize_cnf(prop: &Proposition) -> Proposition {
+    let table = TruthTable::new(prop);
+    let maxterms: Vec<u64> = (0..).zip(table.rows()).filter(|(_, row)| !row.result).map(|(index, _)| index).collect();
+    let cover = minimum_cover(&primes(table.atoms().len(), maxterms.iter().copied()), &maxterms);
+    from_cnf(&to_literals(table.atoms(), &cover, false))
+}
+
+/// A smallest DNF with the given truth function: `outputs[i]` is its value on row `i` of the truth table
+/// over `atoms`, counting up in binary with the first atom as the most significant bit, as `TruthTable`
+/// lists them. There must be an output for each of the 2^n rows.
+pub fn from_truth_function(outputs: &[bool], atoms: &[char]) -> Proposition {
+    let outputs: Vec<Option<bool>> = outputs.iter().map(|output| Some(*output)).collect();
+    from_truth_function_with_dc(&outputs, atoms)
+}
+
+/// As `from_truth_function`, for an incompletely specified function: a `None` output is a don't-care row,
+/// which the formula may make true or false, whichever gives fewer terms and then fewer literals.
+pub fn from_truth_function_with_dc(outputs: &[Option<bool>], atoms: &[char]) -> Proposition {
+    assert_eq!(outputs.len(), 1 << atoms.len(), "expected an output for each row over {} atoms", atoms.len());
+    let rows = || (0..).zip(outputs);
+    let minterms: Vec<u64> = rows().filter(|(_, output)| **output == Some(true)).map(|(index, _)| index).collect();
+    let allowed = rows().filter(|(_, output)| **output != Some(false)).map(|(index, _)| index);
+    let cover = minimum_cover(&primes(atoms.len(), allowed), &minterms);
+    Proposition::disjunction(to_literals(atoms, &cover, true).iter().map(|term| Proposition::conjunction(term.iter().map(Literal::to_proposition))))
+}
+
+/// One formula for each of the 2^(2^n) truth functions of the first `n` of the atoms `P`, `Q`, `R` and
+/// `S`, each a smallest DNF as `from_truth_function` gives it. They're listed by output column read as a
+/// binary number, top row most significant, so `⊥` comes first and `⊤` last. No two are equivalent, and
+/// every formula over those atoms is equivalent to one of them. Beyond four atoms there are too many.
+pub fn distinct_functions(n: usize) -> Vec<Proposition> {
+    let atoms = ['P', 'Q', 'R', 'S'];
+    assert!(n <= atoms.len(), "2^(2^{}) truth functions are too many to list", n);
+    let rows = 1usize << n;
+    (0u64..1 << rows)
+        .map(|function| {
+            let outputs: Vec<bool> = (0..rows).map(|row| function & (1 << (rows - 1 - row)) != 0).collect();
+            from_truth_function(&outputs, &atoms[..n])
+        })
+        .collect()
+}
+
+// The prime cubes covering exactly the given rows.
+pub(crate) fn primes(atoms: usize, rows: impl IntoIterator<Item = u64>) -> Vec<Cube> {
+    let all = (1u64 << atoms) - 1;
+    let mut cubes: BTreeSet<Cube> = rows.into_iter().map(|row| (all, row)).collect();
+    let mut primes = BTreeSet::new();
+    while !cubes.is_empty() {
+        let mut merged = BTreeSet::new();
+        let mut used = BTreeSet::new();
+        for (care, values) in &cubes {
+            for bit in (0..atoms).map(|bit| 1u64 << bit).filter(|bit| care & bit != 0 && values & bit == 0) {
+                let partner = (*care, values | bit);
+                if cubes.contains(&partner) {
+                    merged.insert((care & !bit, *values));
+                    used.insert((*care, *values));
+                    used.insert(partner);
+                }
+            }
+        }
+        primes.extend(cubes.difference(&used).copied());
+        cubes = merged;
+    }
+    primes.into_iter().collect()
+}
+
+// The cheapest subset of `cubes` covering every row of `rows`, costing fewest cubes and then fewest literals.
+pub(crate) fn minimum_cover(cubes: &[Cube], rows: &[u64]) -> Vec<Cube> {
+    fn cost(cover: &[Cube]) -> (usize, u32) {
+        (cover.len(), cover.iter().map(|(care, _)| care.count_ones()).sum())
+    }
+    fn search(cubes: &[Cube], rows: &[u64], chosen: &mut Vec<Cube>, best: &mut Option<Vec<Cube>>) {
+        if best.as_ref().is_some_and(|best| cost(chosen) >= cost(best)) {
+            return;
+        }
+        let uncovered = rows.iter().find(|row| !chosen.iter().any(|cube| covers(*cube, **row)));
+        let row = match uncovered {
+            Some(row) => *row,
+            None => {
+                *best = Some(chosen.clone());
+                return;
+            }
+        };
+        for cube in cubes.iter().filter(|cube| covers(**cube, row)) {
+            chosen.push(*cube);
+            search(cubes, rows, chosen, best);
+            chosen.pop();
+        }
+    }
+    let mut best = None;
+    search(cubes, rows, &mut Vec::new(), &mut best);
+    let mut cover = best.expect("the primes of a set of rows cover it");
+    cover.sort();
+    cover
+}
+
+fn covers((care, values): Cube, row: u64) -> bool {
+    row & care == values
+}
+
+// Each cube as its literals, negated unless `positive`, shortest first.
+pub(crate) fn to_literals(atoms: &[char], cubes: &[Cube], positive: bool) -> Vec<Vec<Literal>> {
+    let count = atoms.len();
+    let mut terms: Vec<Vec<Literal>> = cubes
+        .iter()
+        .map(|(care, values)| {
+            let mut term: Vec<Literal> = (0..count)
+                .filter(|column| care & (1 << (count - 1 - column)) != 0)
+                .map(|column| Literal { atom: atoms[column], positive: (values & (1 << (count - 1 - column)) != 0) == positive })
+                .collect();
+            term.sort();
+            term
+        })
+        .collect();
+    terms.sort_by(|left, right| left.len().cmp(&right.len()).then_with(|| left.cmp(right)));
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{equivalent, eval_rpn, parse_formula, to_rpn};
+
+    fn literal(atom: char, positive: bool) -> Literal {
+        Literal { atom, positive }
+    }
+
+    #[test]
+    fn finds_prime_implicants() {
+        let prop = parse_formula("(P ∧ Q) ∨ (R ∧ ¬P)").unwrap();
+        let expected = vec![vec![literal('P', false), literal('R', true)], vec![literal('P', true), literal('Q', true)], vec![literal('Q', true), literal('R', true)]];
+        assert_eq!(prime_implicants(&prop), expected);
+        assert_eq!(prime_implicants(&parse_formula("P ∨ ¬P").unwrap()), vec![vec![]]);
+    }
+
+    #[test]
+    fn finds_prime_implicates_equivalent_to_the_formula() {
+        let prop = parse_formula("P ∨ (Q ∧ ¬R)").unwrap();
+        let implicates = prime_implicates(&prop);
+        assert_eq!(implicates, vec![vec![literal('P', true), literal('Q', true)], vec![literal('P', true), literal('R', false)]]);
+        let rows = TruthTable::new(&prop).rows();
+        assert_eq!(TruthTable::new(&from_cnf(&implicates)).rows(), rows);
+        assert_eq!(prime_implicates(&parse_formula("P ∧ ¬P").unwrap()), vec![vec![]]);
+    }
+
+    #[test]
+    fn minimizes_redundant_cnfs() {
+        let prop = parse_formula("(P ∨ Q) ∧ (P ∨ ¬Q) ∧ (Q ∨ R ∨ P) ∧ (P ∨ R) ∧ (R ∨ Q)").unwrap();
+        let minimal = minimize_cnf(&prop);
+        assert_eq!(minimal.to_string(), "P ∧ (Q ∨ R)");
+        assert_eq!(TruthTable::new(&minimal).rows(), TruthTable::new(&prop).rows());
+        assert_eq!(minimize_cnf(&parse_formula("P ∨ ¬P").unwrap()), Proposition::constant(true));
+        assert_eq!(minimize_cnf(&parse_formula("P ∧ ¬P").unwrap()), Proposition::constant(false));
+    }
+
+    #[test]
+    fn synthesizes_smaller_formulas_with_dont_cares() {
+        // True on P ∧ Q and on P ∧ R; the row P ∧ ¬Q ∧ ¬R is a don't-care.
+        let outputs = [Some(false), Some(false), Some(false), Some(false), None, Some(true), Some(true), Some(true)];
+        let atoms = ['P', 'Q', 'R'];
+        let specified = from_truth_function(&outputs.map(|output| output.unwrap_or(false)), &atoms);
+        assert_eq!(specified.to_string(), "(P ∧ Q) ∨ (P ∧ R)");
+        let relaxed = from_truth_function_with_dc(&outputs, &atoms);
+        assert_eq!(relaxed, Proposition::variable('P'));
+        assert!(relaxed.size() < specified.size());
+
+        for (index, output) in outputs.iter().enumerate() {
+            let env = atoms.iter().enumerate().map(|(column, atom)| (*atom, index & (4 >> column) != 0)).collect();
+            assert!(output.is_none_or(|output| eval_rpn(&to_rpn(&relaxed), &env) == Ok(output)));
+        }
+        assert_eq!(from_truth_function_with_dc(&[None, None], &['P']), Proposition::constant(false));
+    }
+
+    #[test]
+    fn lists_one_formula_per_truth_function() {
+        let unary: Vec<String> = distinct_functions(1).iter().map(Proposition::to_string).collect();
+        assert_eq!(unary, vec!["⊥", "P", "¬P", "⊤"]);
+
+        let binary = distinct_functions(2);
+        assert_eq!(binary.len(), 16);
+        for (index, left) in binary.iter().enumerate() {
+            for right in &binary[index + 1..] {
+                assert!(!equivalent(left, right), "{} and {}", left, right);
+            }
+        }
+        assert!(binary.iter().any(|prop| equivalent(prop, &parse_formula("P ↔ Q").unwrap())));
+        assert_eq!(distinct_functions(0), vec![Proposition::constant(false), Proposition::constant(true)]);
+    }
+}
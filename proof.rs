@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::Proposition;
+
+/// A line of a derivation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub formula: Proposition,
+    /// The rule or reason, such as `premise` or `→E`.
+    pub justification: String,
+    /// The (0-based) indices of the earlier steps this one follows from.
+    pub from: Vec<usize>,
+    /// How many subproofs the step is nested in.
+    pub depth: usize,
+}
+
+/// A linear, Fitch-style derivation whose last step is its conclusion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Proof {
+    steps: Vec<Step>,
+    depth: usize,
+}
+
+impl Proof {
+    pub fn new() -> Proof {
+        Proof::default()
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Adds a step in the current subproof, returning its index.
+    pub fn add(&mut self, formula: Proposition, justification: &str, from: &[usize]) -> usize {
+        self.steps.push(Step { formula, justification: justification.to_string(), from: from.to_vec(), depth: self.depth });
+        self.steps.len() - 1
+    }
+
+    /// Opens a subproof whose first step assumes `formula`.
+    pub fn assume(&mut self, formula: Proposition) -> usize {
+        self.depth += 1;
+        self.add(formula, "assumption", &[])
+    }
+
+    /// Closes the innermost subproof with a step after it, such as the `→I` discharging its assumption.
+    pub fn discharge(&mut self, formula: Proposition, justification: &str, from: &[usize]) -> usize {
+        self.depth = self.depth.checked_sub(1).expect("no subproof to close");
+        self.add(formula, justification, from)
+    }
+
+    /// Numbered lines, with a `| ` bar per enclosing subproof and the justifications in a column:
+    ///
+    /// ```text
+    /// 1. P → Q    premise
+    /// 2. | P      assumption
+    /// ```
+    ///
+    /// Step references are printed 1-based, like the line numbers.
+    pub fn to_text(&self) -> String {
+        let number_width = self.steps.len().to_string().len();
+        let lines: Vec<String> = self.steps.iter().map(|step| format!("{}{}", "| ".repeat(step.depth), step.formula)).collect();
+        let line_width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut text = String::new();
+        for (index, (step, line)) in self.steps.iter().zip(&lines).enumerate() {
+            let padding = line_width - line.chars().count();
+            writeln!(text, "{:>width$}. {}{}    {}", index + 1, line, " ".repeat(padding), justify(step), width = number_width).unwrap();
+        }
+        text
+    }
+
+    /// The conclusion at the root with the steps it follows from beneath it, recursively:
+    ///
+    /// ```text
+    /// Q  [→E 1, 2]
+    /// |-- P → Q  [premise]
+    /// `-- P  [premise]
+    /// ```
+    ///
+    /// A step used more than once appears under each use.
+    pub fn to_tree(&self) -> String {
+        let mut tree = String::new();
+        if let Some(last) = self.steps.len().checked_sub(1) {
+            self.write_tree(&mut tree, last, "", "");
+        }
+        tree
+    }
+
+    fn write_tree(&self, tree: &mut String, index: usize, lead: &str, indent: &str) {
+        let step = &self.steps[index];
+        writeln!(tree, "{}{}  [{}]", lead, step.formula, justify(step)).unwrap();
+        for (position, from) in step.from.iter().enumerate() {
+            let last = position + 1 == step.from.len();
+            let (lead, next_indent) = if last { ("`-- ", "    ") } else { ("|-- ", "|   ") };
+            self.write_tree(tree, *from, &format!("{}{}", indent, lead), &format!("{}{}", indent, next_indent));
+        }
+    }
+}
+
+fn justify(step: &Step) -> String {
+    let lines: Vec<String> = step.from.iter().map(|from| (from + 1).to_string()).collect();
+    if lines.is_empty() {
+        step.justification.clone()
+    } else {
+        format!("{} {}", step.justification, lines.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    // Hypothetical syllogism: from P → Q and Q → R, derive P → R.
+    fn syllogism() -> Proof {
+        let formula = |source| parse_formula(source).unwrap();
+        let mut proof = Proof::new();
+        let first = proof.add(formula("P → Q"), "premise", &[]);
+        let second = proof.add(formula("Q → R"), "premise", &[]);
+        let assumption = proof.assume(formula("P"));
+        let q = proof.add(formula("Q"), "→E", &[first, assumption]);
+        let r = proof.add(formula("R"), "→E", &[second, q]);
+        proof.discharge(formula("P → R"), "→I", &[assumption, r]);
+        proof
+    }
+
+    #[test]
+    fn renders_proofs_as_text() {
+        let expected = "\
+1. P → Q    premise
+2. Q → R    premise
+3. | P      assumption
+4. | Q      →E 1, 3
+5. | R      →E 2, 4
+6. P → R    →I 3, 5
+";
+        assert_eq!(syllogism().to_text(), expected);
+    }
+
+    #[test]
+    fn renders_proofs_as_trees() {
+        let expected = "\
+P → R  [→I 3, 5]
+|-- P  [assumption]
+`-- R  [→E 2, 4]
+    |-- Q → R  [premise]
+    `-- Q  [→E 1, 3]
+        |-- P → Q  [premise]
+        `-- P  [assumption]
+";
+        assert_eq!(syllogism().to_tree(), expected);
+        assert_eq!(Proof::new().to_tree(), "");
+    }
+}
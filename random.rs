@@ -0,0 +1,46 @@
+/// A seeded pseudo-random generator (SplitMix64) for the randomized searches. Each takes its seed
+/// explicitly, so the same seed draws the same sequence, and finds the same result, on every run.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut mixed = self.state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// A number from `0..bound`, which must not be empty.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "nothing to choose from");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub(crate) fn coin(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_its_draws_for_a_seed() {
+        let draws = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..8).map(|_| rng.below(100)).collect::<Vec<usize>>()
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+        assert!(draws(7).iter().all(|draw| *draw < 100));
+    }
+}
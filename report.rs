@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::search::{find_model, signature};
+use crate::{atoms, evaluate_at, from_cnf, simplify, to_cnf, to_nnf, FrameFamily, Model, Proposition, World};
+
+// The largest models searched when classifying a formula with modal operators or nominals.
+const MODAL_WORLDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    Valid,
+    Contradiction,
+    Contingent,
+}
+
+/// Everything `analyze` works out about a formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub formula: Proposition,
+    pub atoms: BTreeSet<char>,
+    pub size: usize,
+    pub modal_depth: usize,
+    pub classification: Classification,
+    /// A smallest model and a world of it where the formula holds.
+    pub satisfying_model: Option<(Model, World)>,
+    pub nnf: Proposition,
+    /// The CNF as a formula, for formulas without modal operators.
+    pub cnf: Option<Proposition>,
+}
+
+/// Sizes up `prop`. Propositional formulas are classified exactly. Formulas with modal operators or
+/// nominals are classified by searching every model of up to 3 worlds, so for them `Valid` and
+/// `Contradiction` mean that no such model says otherwise.
+pub fn analyze(prop: &Proposition) -> Report {
+    let atoms = atoms(prop);
+    let cnf = to_cnf(prop);
+    let props = std::slice::from_ref(prop);
+    let propositional = cnf.is_some() && signature(props).1.is_empty();
+    let max_worlds = if propositional { 1 } else { MODAL_WORLDS };
+    let satisfying_model = find_model(props, FrameFamily::All, max_worlds, |model| model.worlds().find(|world| evaluate_at(model, *world, prop)));
+    let falsifiable = find_model(props, FrameFamily::All, max_worlds, |model| model.worlds().find(|world| !evaluate_at(model, *world, prop))).is_some();
+    let classification = match (satisfying_model.is_some(), falsifiable) {
+        (false, _) => Classification::Contradiction,
+        (true, false) => Classification::Valid,
+        (true, true) => Classification::Contingent,
+    };
+    Report {
+        formula: prop.clone(),
+        atoms,
+        size: prop.size(),
+        modal_depth: prop.modal_depth(),
+        classification,
+        satisfying_model,
+        nnf: simplify(&to_nnf(prop)),
+        cnf: cnf.map(|clauses| from_cnf(&clauses)),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let atoms: Vec<String> = self.atoms.iter().map(|atom| atom.to_string()).collect();
+        let classification = match self.classification {
+            Classification::Valid => "valid",
+            Classification::Contradiction => "contradiction",
+            Classification::Contingent => "contingent",
+        };
+        writeln!(f, "formula:        {}", self.formula)?;
+        writeln!(f, "atoms:          {}", if atoms.is_empty() { "none".to_string() } else { atoms.join(", ") })?;
+        writeln!(f, "size:           {}", self.size)?;
+        writeln!(f, "modal depth:    {}", self.modal_depth)?;
+        writeln!(f, "classification: {}", classification)?;
+        match &self.satisfying_model {
+            Some((model, world)) => writeln!(f, "satisfied at:   world {} of {}", world, describe(model))?,
+            None => writeln!(f, "satisfied at:   no world")?,
+        }
+        writeln!(f, "nnf:            {}", self.nnf)?;
+        match &self.cnf {
+            Some(cnf) => writeln!(f, "cnf:            {}", cnf),
+            None => writeln!(f, "cnf:            none, the formula is modal"),
+        }
+    }
+}
+
+// A one-line description such as `0 [P], 1 [] with 0 -> 1`.
+fn describe(model: &Model) -> String {
+    let worlds: Vec<String> = model
+        .worlds()
+        .map(|world| {
+            let true_atoms: Vec<String> = model.atoms().into_iter().filter(|atom| model.holds(world, *atom)).map(|atom| atom.to_string()).collect();
+            let names: String = model.names().filter(|(_, named)| *named == world).map(|(name, _)| format!(" ({})", name)).collect();
+            format!("{}{} [{}]", world, names, true_atoms.join(", "))
+        })
+        .collect();
+    let edges: Vec<String> = model.worlds().flat_map(|world| model.successors(world).map(move |next| format!("{} -> {}", world, next))).collect();
+    if edges.is_empty() {
+        worlds.join(", ")
+    } else {
+        format!("{} with {}", worlds.join(", "), edges.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    #[test]
+    fn reports_on_a_propositional_formula() {
+        let report = analyze(&parse_formula("(P → Q) ∧ ¬Q").unwrap());
+        assert_eq!(report.atoms, BTreeSet::from(['P', 'Q']));
+        assert_eq!((report.size, report.modal_depth), (6, 0));
+        assert_eq!(report.classification, Classification::Contingent);
+        let (model, world) = report.satisfying_model.clone().unwrap();
+        assert!(!model.holds(world, 'P') && !model.holds(world, 'Q'));
+        let expected = "\
+formula:        (P → Q) ∧ ¬Q
+atoms:          P, Q
+size:           6
+modal depth:    0
+classification: contingent
+satisfied at:   world 0 of 0 []
+nnf:            (¬P ∨ Q) ∧ ¬Q
+cnf:            (¬P ∨ Q) ∧ ¬Q
+";
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn classifies_modal_formulas_by_bounded_search() {
+        let report = analyze(&parse_formula("(□(P → Q)) → ((□P) → □Q)").unwrap());
+        assert_eq!(report.classification, Classification::Valid);
+        assert_eq!(report.modal_depth, 1);
+        assert_eq!(report.cnf, None);
+        assert_eq!(analyze(&parse_formula("(◇P) ∧ □¬P").unwrap()).classification, Classification::Contradiction);
+        let report = analyze(&parse_formula("◇P").unwrap());
+        assert!(report.to_string().contains("satisfied at:   world 0 of 0 [P] with 0 -> 0\n"));
+    }
+}
@@ -0,0 +1,490 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::{atoms, Atom, Connective, Proposition};
+
+/// Replaces every occurrence of the atom `atom` in `prop` with `replacement`.
+pub fn substitute(prop: &Proposition, atom: char, replacement: &Proposition) -> Proposition {
+    match prop {
+        Proposition::Atom(Atom::Variable(name)) if *name == atom => replacement.clone(),
+        _ => prop.with_operands(prop.operands().into_iter().map(|operand| substitute(operand, atom, replacement)).collect()),
+    }
+}
+
+/// An equivalent formula with the constants folded away, double negations removed, identical and
+/// complementary operands merged (`φ ∧ φ` is `φ`, `φ ∨ ¬φ` is `⊤`) and parentheses dropped. Under a modal operator only the
+/// constants that it can't change are folded: `□⊤`, `◇⊥`, `A ⊤` and `E ⊥`.
+pub fn simplify(prop: &Proposition) -> Proposition {
+    simplify_with(prop, TransformOptions::default())
+}
+
+/// `simplify`, recursing only as deep as `options` allows.
+pub fn simplify_with(prop: &Proposition, options: TransformOptions) -> Proposition {
+    let identifiers = RefCell::new(Identifiers::default());
+    fold(prop, (), options, &|prop, ()| prop.operands().into_iter().map(|operand| (operand, ())).collect(), &|prop, (), operands| {
+        identifiers.borrow_mut().simplified(prop, operands)
+    })
+    .prop
+}
+
+// A simplified formula, with an identifier it shares with exactly the formulas equal to it, so that
+// operands are compared without walking through them. A negation also has its operand's identifier.
+struct Simplified {
+    prop: Proposition,
+    id: usize,
+    negates: Option<usize>,
+}
+
+// The identifiers handed out so far, keyed by a node's connective (as it is with `⊤` operands) or
+// atom, and its operands' identifiers.
+#[derive(Default)]
+struct Identifiers(HashMap<(Proposition, Vec<usize>), usize>);
+
+impl Identifiers {
+    // `prop`, whose operands have the identifiers `operands`, with its own identifier.
+    fn identify(&mut self, prop: Proposition, operands: Vec<usize>) -> Simplified {
+        let negates = match prop {
+            Proposition::Connective(Connective::Not(_)) => Some(operands[0]),
+            _ => None,
+        };
+        let shape = prop.with_operands(vec![Proposition::constant(true); operands.len()]);
+        let next = self.0.len();
+        let id = *self.0.entry((shape, operands)).or_insert(next);
+        Simplified { prop, id, negates }
+    }
+
+    fn value(&mut self, value: bool) -> Simplified {
+        self.identify(Proposition::constant(value), vec![])
+    }
+
+    // `prop` with its operands replaced by their simplifications, `operands`, and then simplified itself.
+    fn simplified(&mut self, prop: &Proposition, operands: Vec<Simplified>) -> Simplified {
+        let complementary = |left: &Simplified, right: &Simplified| left.negates == Some(right.id) || right.negates == Some(left.id);
+        let mut operands = operands.into_iter();
+        match (prop, operands.next(), operands.next()) {
+            (Proposition::Parenthesised(_), Some(prop), None) => prop,
+            (Proposition::Connective(Connective::Not(_)), Some(operand), None) => self.negated(operand),
+            (Proposition::Connective(Connective::And(..)), Some(left), Some(right)) => match (constant(&left.prop), constant(&right.prop)) {
+                (Some(false), _) | (_, Some(false)) => self.value(false),
+                (Some(true), _) => right,
+                (_, Some(true)) => left,
+                _ if left.id == right.id => left,
+                _ if complementary(&left, &right) => self.value(false),
+                _ => self.identify(Proposition::and(left.prop, right.prop), vec![left.id, right.id]),
+            },
+            (Proposition::Connective(Connective::Or(..)), Some(left), Some(right)) => match (constant(&left.prop), constant(&right.prop)) {
+                (Some(true), _) | (_, Some(true)) => self.value(true),
+                (Some(false), _) => right,
+                (_, Some(false)) => left,
+                _ if left.id == right.id => left,
+                _ if complementary(&left, &right) => self.value(true),
+                _ => self.identify(Proposition::or(left.prop, right.prop), vec![left.id, right.id]),
+            },
+            (Proposition::Connective(Connective::IfThen(..)), Some(left), Some(right)) => match (constant(&left.prop), constant(&right.prop)) {
+                (Some(false), _) | (_, Some(true)) => self.value(true),
+                (Some(true), _) => right,
+                (_, Some(false)) => self.negated(left),
+                _ if left.id == right.id => self.value(true),
+                _ => self.identify(Proposition::if_then(left.prop, right.prop), vec![left.id, right.id]),
+            },
+            (Proposition::Connective(Connective::Iff(..)), Some(left), Some(right)) => match (constant(&left.prop), constant(&right.prop)) {
+                (Some(true), _) => right,
+                (_, Some(true)) => left,
+                (Some(false), _) => self.negated(right),
+                (_, Some(false)) => self.negated(left),
+                _ if left.id == right.id => self.value(true),
+                _ if complementary(&left, &right) => self.value(false),
+                _ => self.identify(Proposition::iff(left.prop, right.prop), vec![left.id, right.id]),
+            },
+            (Proposition::Connective(Connective::Necessarily(_) | Connective::Everywhere(_)), Some(operand), None) if constant(&operand.prop) == Some(true) => {
+                self.value(true)
+            }
+            (Proposition::Connective(Connective::Possibly(_) | Connective::Somewhere(_)), Some(operand), None) if constant(&operand.prop) == Some(false) => {
+                self.value(false)
+            }
+            (prop, first, second) => {
+                let (props, ids) = first.into_iter().chain(second).map(|operand| (operand.prop, operand.id)).unzip();
+                self.identify(prop.with_operands(props), ids)
+            }
+        }
+    }
+
+    // The negation of the simplified `prop`, simplified.
+    fn negated(&mut self, prop: Simplified) -> Simplified {
+        match (constant(&prop.prop), prop.prop) {
+            (Some(value), _) => self.value(!value),
+            // A simplified formula never negates a negation, so the operand isn't one.
+            (_, Proposition::Connective(Connective::Not(operand))) => Simplified { prop: *operand, id: prop.negates.expect("a negation's operand"), negates: None },
+            (_, negated) => self.identify(Proposition::negation(negated), vec![prop.id]),
+        }
+    }
+}
+
+fn constant(prop: &Proposition) -> Option<bool> {
+    match prop {
+        Proposition::Atom(Atom::True) => Some(true),
+        Proposition::Atom(Atom::False) => Some(false),
+        _ => None,
+    }
+}
+
+/// An equivalent formula in negation normal form: `→` and `↔` are expanded, parentheses dropped, and
+/// negations pushed inwards to the atoms through the connectives and their modal duals (`¬□φ` is
+/// `◇¬φ`, `¬A φ` is `E ¬φ`, `¬@i φ` is `@i ¬φ`). `D`, `K_a`, `C_G`, `X`, `Y` and `□→` have no duals in the language, so
+/// their negations stay put, with their operands themselves in negation normal form.
+pub fn to_nnf(prop: &Proposition) -> Proposition {
+    to_nnf_with(prop, TransformOptions::default())
+}
+
+/// `to_nnf`, recursing only as deep as `options` allows.
+pub fn to_nnf_with(prop: &Proposition, options: TransformOptions) -> Proposition {
+    fold(prop, true, options, &nnf_operands, &nnf)
+}
+
+/// Abstracts `prop` to propositional logic: each maximal modal-headed subformula (headed by `□`, `◇`,
+/// or any of the other operators besides the Boolean connectives) becomes a fresh atom, with equal
+/// subformulas sharing one. Returns the skeleton and what each fresh atom stands for; substituting
+/// those back gives `prop` again. Fresh atoms are Unicode private-use characters the formula doesn't
+/// use, in order from U+E000, so they never clash with a letter the parsers read.
+///
+/// # Panics
+///
+/// If `prop` has more distinct modal-headed subformulas than there are private-use characters, 137,468.
+pub fn propositional_skeleton(prop: &Proposition) -> (Proposition, HashMap<char, Proposition>) {
+    let used = atoms(prop);
+    let private_use = ('\u{E000}'..='\u{F8FF}').chain('\u{F0000}'..='\u{FFFFD}').chain('\u{100000}'..='\u{10FFFD}');
+    let mut fresh = private_use.filter(|atom| !used.contains(atom));
+    let mut abstracted: Vec<(Proposition, char)> = Vec::new();
+    let skeleton = skeleton(prop, &mut |modal| {
+        if let Some((_, atom)) = abstracted.iter().find(|(seen, _)| seen == modal) {
+            return *atom;
+        }
+        let atom = fresh.next().expect("too many distinct modal subformulas for the fresh atoms");
+        abstracted.push((modal.clone(), atom));
+        atom
+    });
+    (skeleton, abstracted.into_iter().map(|(modal, atom)| (atom, modal)).collect())
+}
+
+fn skeleton(prop: &Proposition, abstract_modal: &mut impl FnMut(&Proposition) -> char) -> Proposition {
+    match prop {
+        Proposition::Atom(_) => prop.clone(),
+        Proposition::Connective(
+            Connective::And(..) | Connective::Or(..) | Connective::IfThen(..) | Connective::Iff(..) | Connective::Not(_),
+        )
+        | Proposition::Parenthesised(_) => prop.with_operands(prop.operands().into_iter().map(|operand| skeleton(operand, abstract_modal)).collect()),
+        Proposition::Connective(_) => Proposition::variable(abstract_modal(prop)),
+    }
+}
+
+/// Whether an atom occurs only positively, only negatively, or both ways in a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    Positive,
+    Negative,
+    Mixed,
+}
+
+/// The polarity of each atom and nominal of `prop`: the sign its occurrences take in `to_nnf(prop)`,
+/// negative where they end up under a `¬`. Both sides of a `↔` and the antecedent of a `□→` are mixed.
+/// Positive-only atoms can be set true and negative-only ones false without making the formula any
+/// less satisfiable.
+pub fn polarity_map(prop: &Proposition) -> HashMap<char, Polarity> {
+    let mut polarities = HashMap::new();
+    record_polarities(prop, Some(true), &mut polarities);
+    polarities
+}
+
+// Records the atoms of `prop`, which occurs positively, negatively or (for `None`) both ways.
+fn record_polarities(prop: &Proposition, sign: Option<bool>, polarities: &mut HashMap<char, Polarity>) {
+    let flipped = sign.map(|positive| !positive);
+    match prop {
+        Proposition::Atom(Atom::Variable(name) | Atom::Nominal(name)) => {
+            let polarity = match sign {
+                Some(true) => Polarity::Positive,
+                Some(false) => Polarity::Negative,
+                None => Polarity::Mixed,
+            };
+            let recorded = polarities.entry(*name).or_insert(polarity);
+            if *recorded != polarity {
+                *recorded = Polarity::Mixed;
+            }
+        }
+        Proposition::Connective(Connective::Not(prop)) => record_polarities(prop, flipped, polarities),
+        Proposition::Connective(Connective::IfThen(left, right)) => {
+            record_polarities(left, flipped, polarities);
+            record_polarities(right, sign, polarities);
+        }
+        Proposition::Connective(Connective::Iff(left, right)) => {
+            record_polarities(left, None, polarities);
+            record_polarities(right, None, polarities);
+        }
+        Proposition::Connective(Connective::Counterfactual(antecedent, consequent)) => {
+            record_polarities(antecedent, None, polarities);
+            record_polarities(consequent, sign, polarities);
+        }
+        // Every other operator is monotone in its operands.
+        _ => {
+            for operand in prop.operands() {
+                record_polarities(operand, sign, polarities);
+            }
+        }
+    }
+}
+
+// The subformulas whose negation normal forms make up that of `prop` (when `positive`, or of its
+// negation otherwise), each with the sign they're wanted in, in the order `nnf` consumes them.
+fn nnf_operands(prop: &Proposition, positive: bool) -> Vec<(&Proposition, bool)> {
+    match prop {
+        Proposition::Atom(_) => vec![],
+        Proposition::Connective(Connective::And(left, right) | Connective::Or(left, right)) => vec![(left, positive), (right, positive)],
+        Proposition::Connective(Connective::IfThen(left, right)) => vec![(left, !positive), (right, positive)],
+        Proposition::Connective(Connective::Iff(left, right)) => vec![(left, false), (right, true), (left, true), (right, false)],
+        Proposition::Connective(Connective::Not(prop)) => vec![(prop, !positive)],
+        // Operators without a dual keep a negation outside, so their operands are wanted positively.
+        Proposition::Connective(
+            Connective::Elsewhere(prop) | Connective::Knows(_, prop) | Connective::Next(prop) | Connective::Previous(prop) | Connective::CommonKnowledge(_, prop),
+        ) => vec![(prop, true)],
+        Proposition::Connective(Connective::Counterfactual(antecedent, consequent)) => vec![(antecedent, true), (consequent, true)],
+        _ => prop.operands().into_iter().map(|operand| (operand, positive)).collect(),
+    }
+}
+
+// The negation normal form of `prop` when `positive`, and of its negation otherwise, given those of
+// its `nnf_operands`.
+fn nnf(prop: &Proposition, positive: bool, operands: Vec<Proposition>) -> Proposition {
+    let mut operands = operands.into_iter();
+    let mut next = || operands.next().expect("missing operand");
+    let both = |left: Proposition, right: Proposition| if positive { Proposition::and(left, right) } else { Proposition::or(left, right) };
+    let either = |left: Proposition, right: Proposition| if positive { Proposition::or(left, right) } else { Proposition::and(left, right) };
+    let negated_if_negative = |prop: Proposition| if positive { prop } else { Proposition::negation(prop) };
+    match prop {
+        Proposition::Atom(Atom::True | Atom::False) => Proposition::constant((*prop == Proposition::constant(true)) == positive),
+        Proposition::Atom(_) => negated_if_negative(prop.clone()),
+        Proposition::Connective(Connective::And(..)) => both(next(), next()),
+        Proposition::Connective(Connective::Or(..) | Connective::IfThen(..)) => either(next(), next()),
+        // The operands come as ¬left, right, left, ¬right.
+        Proposition::Connective(Connective::Iff(..)) => {
+            let (left_false, right_true, left_true, right_false) = (next(), next(), next(), next());
+            if positive {
+                Proposition::and(Proposition::or(left_false, right_true), Proposition::or(left_true, right_false))
+            } else {
+                Proposition::or(Proposition::and(left_true, right_false), Proposition::and(left_false, right_true))
+            }
+        }
+        Proposition::Connective(Connective::Not(_)) | Proposition::Parenthesised(_) => next(),
+        Proposition::Connective(Connective::Possibly(_)) if positive => Proposition::possibly(next()),
+        Proposition::Connective(Connective::Possibly(_)) => Proposition::necessarily(next()),
+        Proposition::Connective(Connective::Necessarily(_)) if positive => Proposition::necessarily(next()),
+        Proposition::Connective(Connective::Necessarily(_)) => Proposition::possibly(next()),
+        Proposition::Connective(Connective::Everywhere(_)) if positive => Proposition::everywhere(next()),
+        Proposition::Connective(Connective::Everywhere(_)) => Proposition::somewhere(next()),
+        Proposition::Connective(Connective::Somewhere(_)) if positive => Proposition::somewhere(next()),
+        Proposition::Connective(Connective::Somewhere(_)) => Proposition::everywhere(next()),
+        Proposition::Connective(Connective::At(nominal, _)) => Proposition::at(*nominal, next()),
+        Proposition::Connective(Connective::Elsewhere(_)) => negated_if_negative(Proposition::elsewhere(next())),
+        Proposition::Connective(Connective::Knows(agent, _)) => negated_if_negative(Proposition::knows(*agent, next())),
+        Proposition::Connective(Connective::Next(_)) => negated_if_negative(Proposition::next(next())),
+        Proposition::Connective(Connective::Previous(_)) => negated_if_negative(Proposition::previous(next())),
+        Proposition::Connective(Connective::CommonKnowledge(group, _)) => negated_if_negative(Proposition::common_knowledge(*group, next())),
+        // A counterfactual has no dual to push a negation into.
+        Proposition::Connective(Connective::Counterfactual(..)) => negated_if_negative(Proposition::counterfactual(next(), next())),
+    }
+}
+
+/// How deep the formula transforms (`to_nnf`, `to_cnf`, `simplify`) recurse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransformOptions {
+    /// The nesting depth to which subformulas are transformed by plain recursion, the quicker way.
+    /// Anything nested deeper is transformed with an explicit work stack on the heap instead, so no
+    /// formula is too deep to transform.
+    pub recursion_limit: usize,
+}
+
+impl Default for TransformOptions {
+    fn default() -> Self {
+        TransformOptions { recursion_limit: 256 }
+    }
+}
+
+// Transforms `prop` bottom-up. `split` lists the subformulas a node's result is made from, each with
+// the context to transform it in, and `join` makes the node's result from theirs.
+pub(crate) fn fold<'a, C: Copy, T>(
+    prop: &'a Proposition,
+    context: C,
+    options: TransformOptions,
+    split: &impl Fn(&'a Proposition, C) -> Vec<(&'a Proposition, C)>,
+    join: &impl Fn(&'a Proposition, C, Vec<T>) -> T,
+) -> T {
+    fold_recursively(prop, context, options.recursion_limit, split, join)
+}
+
+fn fold_recursively<'a, C: Copy, T>(
+    prop: &'a Proposition,
+    context: C,
+    depth_left: usize,
+    split: &impl Fn(&'a Proposition, C) -> Vec<(&'a Proposition, C)>,
+    join: &impl Fn(&'a Proposition, C, Vec<T>) -> T,
+) -> T {
+    if depth_left == 0 {
+        return fold_iteratively(prop, context, split, join);
+    }
+    let results = split(prop, context).into_iter().map(|(operand, context)| fold_recursively(operand, context, depth_left - 1, split, join)).collect();
+    join(prop, context, results)
+}
+
+fn fold_iteratively<'a, C: Copy, T>(
+    prop: &'a Proposition,
+    context: C,
+    split: &impl Fn(&'a Proposition, C) -> Vec<(&'a Proposition, C)>,
+    join: &impl Fn(&'a Proposition, C, Vec<T>) -> T,
+) -> T {
+    enum Task<'a, C> {
+        Split(&'a Proposition, C),
+        // Join a node whose operands' results are the last `usize` on the results stack.
+        Join(&'a Proposition, C, usize),
+    }
+    let mut tasks = vec![Task::Split(prop, context)];
+    let mut results = Vec::new();
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Split(prop, context) => {
+                let operands = split(prop, context);
+                tasks.push(Task::Join(prop, context, operands.len()));
+                tasks.extend(operands.into_iter().rev().map(|(operand, context)| Task::Split(operand, context)));
+            }
+            Task::Join(prop, context, count) => {
+                let operands = results.split_off(results.len() - count);
+                results.push(join(prop, context, operands));
+            }
+        }
+    }
+    results.pop().expect("the root's result")
+}
+
+/// The Boolean derivative of `prop` with respect to `atom`, `φ[atom/⊤] ⊕ φ[atom/⊥]` (with `⊕` written
+/// `¬(… ↔ …)`), simplified. It's true exactly under the assignments where flipping `atom` changes the
+/// value of `prop`.
+pub fn boolean_derivative(prop: &Proposition, atom: char) -> Proposition {
+    let when_true = substitute(prop, atom, &Proposition::constant(true));
+    let when_false = substitute(prop, atom, &Proposition::constant(false));
+    simplify(&Proposition::negation(Proposition::iff(when_true, when_false)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_enriched_formula, parse_formula, to_cnf, TruthTable};
+
+    #[test]
+    fn simplifies_constants_away() {
+        let simplified = |source| simplify(&parse_formula(source).unwrap()).to_string();
+        assert_eq!(simplified("(P ∧ ¬¬Q) ∨ (R ∧ (P → P))"), "(P ∧ Q) ∨ R");
+        assert_eq!(simplified("□(P ∧ ¬(Q ∧ (Q → Q)))"), "□(P ∧ ¬Q)");
+        assert_eq!(simplified("(Q ∨ ¬Q) ∧ ◇(P ∧ ¬P)"), "⊥");
+        assert_eq!(simplify(&substitute(&parse_formula("◇P").unwrap(), 'P', &Proposition::constant(false))), Proposition::constant(false));
+    }
+
+    #[test]
+    fn pushes_negations_to_the_atoms() {
+        let nnf = |source| to_nnf(&parse_enriched_formula(source).unwrap()).to_string();
+        assert_eq!(nnf("¬(P → □(Q ∧ ¬R))"), "P ∧ ◇(¬Q ∨ R)");
+        assert_eq!(nnf("¬(P ↔ Q)"), "(P ∧ ¬Q) ∨ (¬P ∧ Q)");
+        assert_eq!(nnf("¬(A ◇i)"), "E □¬i");
+        assert_eq!(nnf("¬(@i D ¬P)"), "@i ¬D ¬P");
+    }
+
+    #[test]
+    fn transforms_formulas_too_deep_to_recurse_through() {
+        // ¬¬…¬(P ∧ ¬Q), nested far deeper than the default recursion limit.
+        let depth = 100_000;
+        let deep = (0..depth).fold(parse_formula("P ∧ ¬Q").unwrap(), |prop, _| Proposition::negation(prop));
+        assert_eq!(to_nnf(&deep).to_string(), "P ∧ ¬Q");
+        assert_eq!(simplify(&deep).to_string(), "P ∧ ¬Q");
+        assert_eq!(to_cnf(&deep).map(|clauses| clauses.len()), Some(2));
+        // Dropping it whole would recurse just as deep, so take it apart a layer at a time.
+        let mut deep = deep;
+        while let Proposition::Connective(Connective::Not(prop)) = deep {
+            deep = *prop;
+        }
+
+        // Every limit agrees with plain recursion.
+        let prop = parse_enriched_formula("¬(P → □(Q ↔ ¬R)) ∨ ¬(@i D ¬P)").unwrap();
+        for recursion_limit in [0, 1, 3, 256] {
+            let options = TransformOptions { recursion_limit };
+            assert_eq!(to_nnf_with(&prop, options), to_nnf(&prop));
+            assert_eq!(simplify_with(&prop, options), simplify(&prop));
+        }
+    }
+
+    #[test]
+    fn simplifies_long_conjunctions() {
+        // (P ∨ ⊥) ∧ ((Q ∨ ⊥) ∧ (…(P ∨ ⊥) ∧ end)), nested far deeper than the default recursion limit.
+        let depth = 100_000;
+        let chain = |end: Proposition| {
+            (0..depth).fold(end, |rest, index| Proposition::and(Proposition::or(Proposition::variable(if index % 2 == 0 { 'P' } else { 'Q' }), Proposition::constant(false)), rest))
+        };
+        let collapsing = chain(parse_formula("R ∧ ¬R").unwrap());
+        assert_eq!(simplify(&collapsing), Proposition::constant(false));
+
+        // A chain with nothing to merge keeps every link, without its disjunctions.
+        let unmerged = chain(Proposition::variable('R'));
+        let mut simplified = simplify(&unmerged);
+        let mut links = 0;
+        while let Proposition::Connective(Connective::And(left, right)) = simplified {
+            assert_eq!(*left, Proposition::variable(if links % 2 == 0 { 'Q' } else { 'P' }));
+            simplified = *right;
+            links += 1;
+        }
+        assert_eq!((links, simplified), (depth, Proposition::variable('R')));
+        dismantle(collapsing);
+        dismantle(unmerged);
+    }
+
+    // Drops a deep formula a layer at a time, as dropping it whole would recurse just as deep.
+    fn dismantle(prop: Proposition) {
+        let mut pending = vec![prop];
+        while let Some(prop) = pending.pop() {
+            if let Proposition::Connective(Connective::And(left, right) | Connective::Or(left, right)) = prop {
+                pending.extend([*left, *right]);
+            }
+        }
+    }
+
+    #[test]
+    fn differentiates_formulas() {
+        let derivative = boolean_derivative(&parse_formula("P ∧ Q").unwrap(), 'P');
+        assert_eq!(derivative, Proposition::variable('Q'));
+        assert_eq!(boolean_derivative(&parse_formula("Q ∨ R").unwrap(), 'P'), Proposition::constant(false));
+
+        // P ↔ Q is sensitive to P everywhere; P ∨ (Q ∧ R) only where Q ∧ R is false.
+        assert_eq!(boolean_derivative(&parse_formula("P ↔ Q").unwrap(), 'P'), Proposition::constant(true));
+        let derivative = boolean_derivative(&parse_formula("P ∨ (Q ∧ R)").unwrap(), 'P');
+        assert_eq!(TruthTable::new(&derivative).rows(), TruthTable::new(&parse_formula("¬(Q ∧ R)").unwrap()).rows());
+    }
+
+    #[test]
+    fn classifies_atom_polarities() {
+        let polarities = polarity_map(&parse_formula("(P ∨ Q) ∧ (¬Q ∨ R)").unwrap());
+        assert_eq!(polarities, HashMap::from([('P', Polarity::Positive), ('Q', Polarity::Mixed), ('R', Polarity::Positive)]));
+        let polarities = polarity_map(&parse_formula("¬(P → □¬Q) ∨ (R ↔ S)").unwrap());
+        assert_eq!(polarities, HashMap::from([('P', Polarity::Positive), ('Q', Polarity::Positive), ('R', Polarity::Mixed), ('S', Polarity::Mixed)]));
+        assert_eq!(polarity_map(&parse_formula("P → ¬Q").unwrap())[&'Q'], Polarity::Negative);
+    }
+
+    #[test]
+    fn abstracts_modal_subformulas_to_fresh_atoms() {
+        let prop = parse_formula("□P ∧ (Q → ◇R)").unwrap();
+        let (skeleton, abstracted) = propositional_skeleton(&prop);
+        let (first, second) = ('\u{E000}', '\u{E001}');
+        assert_eq!(skeleton, Proposition::and(Proposition::variable(first), Proposition::Parenthesised(Box::new(Proposition::if_then(Proposition::variable('Q'), Proposition::variable(second))))));
+        assert_eq!(abstracted, HashMap::from([(first, parse_formula("□P").unwrap()), (second, parse_formula("◇R").unwrap())]));
+        let restored = abstracted.iter().fold(skeleton, |restored, (atom, modal)| substitute(&restored, *atom, modal));
+        assert_eq!(restored, prop);
+
+        let (skeleton, abstracted) = propositional_skeleton(&parse_formula("□(P ∧ ◇Q) ∨ ¬□(P ∧ ◇Q)").unwrap());
+        assert_eq!(skeleton, Proposition::or(Proposition::variable('\u{E000}'), Proposition::negation(Proposition::variable('\u{E000}'))));
+        assert_eq!(abstracted.len(), 1);
+        assert_eq!(propositional_skeleton(&parse_formula("P ∧ ¬Q").unwrap()).1, HashMap::new());
+    }
+}
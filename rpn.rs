@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::{Atom, Connective, Group, Proposition};
+
+/// A token of the postfix (reverse-Polish) form of a proposition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token {
+    Atom(Atom),
+    And,
+    Or,
+    IfThen,
+    Iff,
+    Not,
+    Possibly,
+    Necessarily,
+    Everywhere,
+    Somewhere,
+    At(char),
+    Elsewhere,
+    Counterfactual,
+    Knows(char),
+    Next,
+    Previous,
+    CommonKnowledge(Group),
+}
+
+impl Token {
+    pub(crate) fn arity(self) -> usize {
+        match self {
+            Token::Atom(_) => 0,
+            Token::Not | Token::Possibly | Token::Necessarily | Token::Everywhere | Token::Somewhere | Token::At(_) | Token::Elsewhere | Token::Knows(_) | Token::Next | Token::Previous | Token::CommonKnowledge(_) => 1,
+            Token::And | Token::Or | Token::IfThen | Token::Iff | Token::Counterfactual => 2,
+        }
+    }
+}
+
+/// Flattens `prop` into postfix order: operands first, then the connective applied to them.
+/// Parentheses carry no information in postfix form, so they're dropped.
+pub fn to_rpn(prop: &Proposition) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    push_rpn(prop, &mut tokens);
+    tokens
+}
+
+fn push_rpn(prop: &Proposition, tokens: &mut Vec<Token>) {
+    match prop {
+        Proposition::Atom(atom) => tokens.push(Token::Atom(*atom)),
+        Proposition::Connective(Connective::And(left, right)) => push_binary(left, right, Token::And, tokens),
+        Proposition::Connective(Connective::Or(left, right)) => push_binary(left, right, Token::Or, tokens),
+        Proposition::Connective(Connective::IfThen(left, right)) => push_binary(left, right, Token::IfThen, tokens),
+        Proposition::Connective(Connective::Iff(left, right)) => push_binary(left, right, Token::Iff, tokens),
+        Proposition::Connective(Connective::Not(prop)) => push_unary(prop, Token::Not, tokens),
+        Proposition::Connective(Connective::Possibly(prop)) => push_unary(prop, Token::Possibly, tokens),
+        Proposition::Connective(Connective::Necessarily(prop)) => push_unary(prop, Token::Necessarily, tokens),
+        Proposition::Connective(Connective::Everywhere(prop)) => push_unary(prop, Token::Everywhere, tokens),
+        Proposition::Connective(Connective::Somewhere(prop)) => push_unary(prop, Token::Somewhere, tokens),
+        Proposition::Connective(Connective::At(name, prop)) => push_unary(prop, Token::At(*name), tokens),
+        Proposition::Connective(Connective::Elsewhere(prop)) => push_unary(prop, Token::Elsewhere, tokens),
+        Proposition::Connective(Connective::Counterfactual(left, right)) => push_binary(left, right, Token::Counterfactual, tokens),
+        Proposition::Connective(Connective::Knows(agent, prop)) => push_unary(prop, Token::Knows(*agent), tokens),
+        Proposition::Connective(Connective::Next(prop)) => push_unary(prop, Token::Next, tokens),
+        Proposition::Connective(Connective::Previous(prop)) => push_unary(prop, Token::Previous, tokens),
+        Proposition::Connective(Connective::CommonKnowledge(group, prop)) => push_unary(prop, Token::CommonKnowledge(*group), tokens),
+        Proposition::Parenthesised(prop) => push_rpn(prop, tokens),
+    }
+}
+
+fn push_unary(prop: &Proposition, token: Token, tokens: &mut Vec<Token>) {
+    push_rpn(prop, tokens);
+    tokens.push(token);
+}
+
+fn push_binary(left: &Proposition, right: &Proposition, token: Token, tokens: &mut Vec<Token>) {
+    push_rpn(left, tokens);
+    push_rpn(right, tokens);
+    tokens.push(token);
+}
+
+/// Rebuilds a proposition from its postfix form.
+pub fn from_rpn(tokens: &[Token]) -> Result<Proposition> {
+    let mut stack: Vec<Proposition> = Vec::new();
+    for (position, token) in tokens.iter().enumerate() {
+        if stack.len() < token.arity() {
+            return Err(Error::StackUnderflow { position });
+        }
+        let prop = match token {
+            Token::Atom(atom) => Proposition::Atom(*atom),
+            Token::Not => Proposition::negation(stack.pop().unwrap()),
+            Token::Possibly => Proposition::possibly(stack.pop().unwrap()),
+            Token::Necessarily => Proposition::necessarily(stack.pop().unwrap()),
+            Token::Everywhere => Proposition::everywhere(stack.pop().unwrap()),
+            Token::Somewhere => Proposition::somewhere(stack.pop().unwrap()),
+            Token::At(name) => Proposition::at(*name, stack.pop().unwrap()),
+            Token::Elsewhere => Proposition::elsewhere(stack.pop().unwrap()),
+            Token::Knows(agent) => Proposition::knows(*agent, stack.pop().unwrap()),
+            Token::Next => Proposition::next(stack.pop().unwrap()),
+            Token::Previous => Proposition::previous(stack.pop().unwrap()),
+            Token::CommonKnowledge(group) => Proposition::common_knowledge(*group, stack.pop().unwrap()),
+            Token::And => {
+                let (left, right) = pop_operands(&mut stack);
+                Proposition::and(left, right)
+            }
+            Token::Or => {
+                let (left, right) = pop_operands(&mut stack);
+                Proposition::or(left, right)
+            }
+            Token::IfThen => {
+                let (left, right) = pop_operands(&mut stack);
+                Proposition::if_then(left, right)
+            }
+            Token::Iff => {
+                let (left, right) = pop_operands(&mut stack);
+                Proposition::iff(left, right)
+            }
+            Token::Counterfactual => {
+                let (left, right) = pop_operands(&mut stack);
+                Proposition::counterfactual(left, right)
+            }
+        };
+        stack.push(prop);
+    }
+    match stack.len() {
+        0 => Err(Error::EmptyExpression),
+        1 => Ok(stack.pop().unwrap()),
+        count => Err(Error::LeftoverOperands { count }),
+    }
+}
+
+/// Evaluates postfix tokens directly with a stack of truth values, without rebuilding the proposition.
+/// As in `evaluate`, the modal operators have no other worlds to range over here and pass their operand's value through.
+pub fn eval_rpn(tokens: &[Token], env: &HashMap<char, bool>) -> Result<bool> {
+    let mut stack: Vec<bool> = Vec::with_capacity(tokens.len());
+    for (position, token) in tokens.iter().enumerate() {
+        let arity = token.arity();
+        if stack.len() < arity {
+            return Err(Error::StackUnderflow { position });
+        }
+        let start = stack.len() - arity;
+        let value = apply(*token, &stack[start..], env)?;
+        stack.truncate(start);
+        stack.push(value);
+    }
+    match stack.len() {
+        0 => Err(Error::EmptyExpression),
+        1 => Ok(stack[0]),
+        count => Err(Error::LeftoverOperands { count }),
+    }
+}
+
+// The value of `token` applied to the values of its operands, in order.
+pub(crate) fn apply(token: Token, operands: &[bool], env: &HashMap<char, bool>) -> Result<bool> {
+    Ok(match token {
+        Token::Atom(Atom::True) => true,
+        Token::Atom(Atom::False) => false,
+        Token::Atom(Atom::Variable(name)) | Token::Atom(Atom::Nominal(name)) => *env.get(&name).ok_or(Error::UnassignedAtom(name))?,
+        Token::Not => !operands[0],
+        Token::Possibly | Token::Necessarily | Token::Everywhere | Token::Somewhere | Token::At(_) | Token::Knows(_) | Token::Next | Token::Previous | Token::CommonKnowledge(_) => operands[0],
+        Token::Elsewhere => false,
+        Token::And => operands[0] && operands[1],
+        Token::Or => operands[0] || operands[1],
+        Token::IfThen | Token::Counterfactual => !operands[0] || operands[1],
+        Token::Iff => operands[0] == operands[1],
+    })
+}
+
+fn pop_operands(stack: &mut Vec<Proposition>) -> (Proposition, Proposition) {
+    let right = stack.pop().unwrap();
+    let left = stack.pop().unwrap();
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate;
+
+    #[test]
+    fn round_trips_through_rpn() {
+        let prop = Proposition::if_then(
+            Proposition::and(Proposition::variable('P'), Proposition::negation(Proposition::variable('Q'))),
+            Proposition::possibly(Proposition::variable('P')),
+        );
+        let tokens = to_rpn(&prop);
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Atom(Atom::Variable('P')),
+                Token::Atom(Atom::Variable('Q')),
+                Token::Not,
+                Token::And,
+                Token::Atom(Atom::Variable('P')),
+                Token::Possibly,
+                Token::IfThen,
+            ]
+        );
+        assert_eq!(from_rpn(&tokens), Ok(prop));
+    }
+
+    #[test]
+    fn evaluates_propositions_built_from_rpn() {
+        // T F ∨ ¬  is  ¬(T ∨ F)
+        let tokens = [Token::Atom(Atom::True), Token::Atom(Atom::False), Token::Or, Token::Not];
+        assert!(!evaluate(from_rpn(&tokens).unwrap()));
+        let tokens = [Token::Atom(Atom::False), Token::Atom(Atom::True), Token::IfThen];
+        assert!(evaluate(from_rpn(&tokens).unwrap()));
+    }
+
+    #[test]
+    fn rejects_malformed_rpn() {
+        assert_eq!(from_rpn(&[Token::Atom(Atom::True), Token::And]), Err(Error::StackUnderflow { position: 1 }));
+        assert_eq!(from_rpn(&[Token::Atom(Atom::True), Token::Atom(Atom::True)]), Err(Error::LeftoverOperands { count: 2 }));
+        assert_eq!(from_rpn(&[]), Err(Error::EmptyExpression));
+    }
+
+    #[test]
+    fn evaluates_rpn_sequences() {
+        let p = Token::Atom(Atom::Variable('P'));
+        let q = Token::Atom(Atom::Variable('Q'));
+        let env = HashMap::from([('P', true), ('Q', false)]);
+        assert_eq!(eval_rpn(&[p, q, Token::And], &env), Ok(false));
+        assert_eq!(eval_rpn(&[p, q, Token::Or], &env), Ok(true));
+        assert_eq!(eval_rpn(&[q, p, Token::IfThen, Token::Not], &env), Ok(false));
+        assert_eq!(eval_rpn(&[p, Token::Not, q, Token::Iff], &env), Ok(true));
+    }
+
+    #[test]
+    fn agrees_with_evaluating_the_proposition() {
+        let prop = Proposition::iff(
+            Proposition::or(Proposition::variable('P'), Proposition::and(Proposition::variable('Q'), Proposition::variable('R'))),
+            Proposition::and(
+                Proposition::or(Proposition::variable('P'), Proposition::variable('Q')),
+                Proposition::or(Proposition::variable('P'), Proposition::variable('R')),
+            ),
+        );
+        let tokens = to_rpn(&prop);
+        let env = HashMap::from([('P', false), ('Q', true), ('R', false)]);
+        assert_eq!(eval_rpn(&tokens, &env), Ok(true));
+    }
+
+    #[test]
+    fn rejects_malformed_stacks() {
+        let env = HashMap::from([('P', true)]);
+        let p = Token::Atom(Atom::Variable('P'));
+        assert_eq!(eval_rpn(&[p, Token::Iff], &env), Err(Error::StackUnderflow { position: 1 }));
+        assert_eq!(eval_rpn(&[p, p, p, Token::And], &env), Err(Error::LeftoverOperands { count: 2 }));
+        assert_eq!(eval_rpn(&[Token::Atom(Atom::Variable('Q'))], &env), Err(Error::UnassignedAtom('Q')));
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{Error, Result};
+use crate::truth_table::counting;
+use crate::{atoms, eval_rpn, fragment, to_cnf, to_rpn, FormulaBatch, Fragment, Proposition};
+
+/// Whether some assignment to the atoms satisfies every formula of `props` at once, i.e. whether their
+/// conjunction is satisfiable. Modal operators have the propositional reading of `eval_rpn`.
+pub fn is_consistent(props: &[Proposition]) -> bool {
+    consistent_model(props).is_some()
+}
+
+/// An assignment satisfying every formula of `props`, giving a value to each atom occurring in them.
+pub fn consistent_model(props: &[Proposition]) -> Option<HashMap<char, bool>> {
+    let batch = FormulaBatch::new(props);
+    assignments(&atoms_of(props)).find(|env| batch.evaluate(env).expect("every atom is assigned").iter().all(|value| *value))
+}
+
+/// Whether every assignment satisfying all of `premises` satisfies `conclusion`.
+pub fn entails(premises: &[Proposition], conclusion: &Proposition) -> bool {
+    let mut props = premises.to_vec();
+    props.push(Proposition::negation(conclusion.clone()));
+    !is_consistent(&props)
+}
+
+/// Whether `left` and `right` have the same truth value under every assignment.
+pub fn equivalent(left: &Proposition, right: &Proposition) -> bool {
+    !is_consistent(&[Proposition::negation(Proposition::iff(left.clone(), right.clone()))])
+}
+
+/// The indices of the premises that can each be dropped without changing whether `conclusion` follows
+/// from them. Each index is redundant on its own; dropping several at once may break the argument, as
+/// when two premises are equivalent. Removing premises never makes a conclusion follow, so for an
+/// argument that isn't valid every premise is redundant.
+pub fn redundant_premises(premises: &[Proposition], conclusion: &Proposition) -> Vec<usize> {
+    let valid = entails(premises, conclusion);
+    (0..premises.len())
+        .filter(|index| {
+            let others: Vec<Proposition> = premises.iter().enumerate().filter(|(other, _)| other != index).map(|(_, premise)| premise.clone()).collect();
+            entails(&others, conclusion) == valid
+        })
+        .collect()
+}
+
+/// The indices, in increasing order, of a minimal subset of `props` that is still inconsistent: removing
+/// any one of them makes the rest consistent. Consistent sets have no such subset and give an empty one.
+///
+/// Formulas are tried for deletion one at a time, so which minimal subset is found depends on their order.
+pub fn minimal_unsat_subset(props: &[Proposition]) -> Vec<usize> {
+    if is_consistent(props) {
+        return Vec::new();
+    }
+    let mut core: Vec<usize> = (0..props.len()).collect();
+    let mut candidate = 0;
+    while candidate < core.len() {
+        let without: Vec<Proposition> = core.iter().filter(|index| **index != core[candidate]).map(|index| props[*index].clone()).collect();
+        if is_consistent(&without) {
+            candidate += 1;
+        } else {
+            core.remove(candidate);
+        }
+    }
+    core
+}
+
+/// The number of assignments to the atoms of `prop` that satisfy it.
+pub fn model_count(prop: &Proposition) -> u64 {
+    let tokens = to_rpn(prop);
+    assignments(&atoms_of(std::slice::from_ref(prop))).filter(|env| eval_rpn(&tokens, env).expect("every atom is assigned")).count() as u64
+}
+
+/// The sum, over the assignments to the atoms of `prop` that satisfy it, of the product of the weights of
+/// the literals they set: `(P, true)` for `P` true and `(P, false)` for `P` false. Literals missing from
+/// `literal_weights` weigh 1, so with no weights (or all of them 1) this is `model_count`.
+pub fn weighted_model_count(prop: &Proposition, literal_weights: &HashMap<(char, bool), f64>) -> f64 {
+    let tokens = to_rpn(prop);
+    assignments(&atoms_of(std::slice::from_ref(prop)))
+        .filter(|env| eval_rpn(&tokens, env).expect("every atom is assigned"))
+        .map(|env| env.into_iter().map(|literal| literal_weights.get(&literal).copied().unwrap_or(1.0)).product::<f64>())
+        .sum()
+}
+
+/// The Shannon entropy in bits of the value of `prop` when its atoms are independent fair coins: 0 for
+/// a tautology or contradiction, 1 for a balanced function such as a single atom.
+pub fn output_entropy(prop: &Proposition) -> f64 {
+    let probability = model_count(prop) as f64 / 2f64.powi(atoms_of(std::slice::from_ref(prop)).len() as i32);
+    [probability, 1.0 - probability].iter().filter(|p| **p > 0.0).map(|p| -p * p.log2()).sum()
+}
+
+/// Bounds on the probability of `prop` when each atom is independently true with a probability somewhere
+/// in its interval `(lo, hi)`; an atom without one may have any probability. The probability is linear in
+/// each atom's, so the bounds are reached at the intervals' endpoints, and only those are tried: one
+/// weighted model count for each of the 2^n combinations.
+pub fn probability_interval(prop: &Proposition, intervals: &HashMap<char, (f64, f64)>) -> (f64, f64) {
+    let atoms = atoms_of(std::slice::from_ref(prop));
+    assignments(&atoms)
+        .map(|corner| {
+            let weights: HashMap<(char, bool), f64> = corner
+                .into_iter()
+                .flat_map(|(atom, upper)| {
+                    let (lo, hi) = intervals.get(&atom).copied().unwrap_or((0.0, 1.0));
+                    let probability = if upper { hi } else { lo };
+                    [((atom, true), probability), ((atom, false), 1.0 - probability)]
+                })
+                .collect();
+            weighted_model_count(prop, &weights)
+        })
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), probability| (lo.min(probability), hi.max(probability)))
+}
+
+/// Solves a Horn formula by unit propagation, in time linear in the size of its CNF: atoms are made true
+/// only when some clause forces them, which gives the least model when there is one. The assignment
+/// covers every atom of `prop`. Formulas outside `Fragment::Horn` are an `Error::NotHorn`.
+pub fn horn_sat(prop: &Proposition) -> Result<Option<HashMap<char, bool>>> {
+    if fragment(prop) != Fragment::Horn {
+        return Err(Error::NotHorn);
+    }
+    let clauses = to_cnf(prop).expect("Horn formulas are propositional");
+    // For each clause, how many of its negative literals' atoms are not yet known to be true.
+    let mut pending: Vec<usize> = clauses.iter().map(|clause| clause.iter().filter(|literal| !literal.positive).count()).collect();
+    let mut watching: HashMap<char, Vec<usize>> = HashMap::new();
+    for (index, clause) in clauses.iter().enumerate() {
+        for literal in clause.iter().filter(|literal| !literal.positive) {
+            watching.entry(literal.atom).or_default().push(index);
+        }
+    }
+    let mut model: HashMap<char, bool> = atoms(prop).into_iter().map(|atom| (atom, false)).collect();
+    let mut forced: Vec<usize> = (0..clauses.len()).filter(|index| pending[*index] == 0).collect();
+    while let Some(index) = forced.pop() {
+        let head = match clauses[index].iter().find(|literal| literal.positive) {
+            Some(head) => head.atom,
+            None => return Ok(None),
+        };
+        if std::mem::replace(model.get_mut(&head).expect("clause atoms occur in the formula"), true) {
+            continue;
+        }
+        for clause in watching.get(&head).into_iter().flatten() {
+            pending[*clause] -= 1;
+            if pending[*clause] == 0 {
+                forced.push(*clause);
+            }
+        }
+    }
+    Ok(Some(model))
+}
+
+pub(crate) fn atoms_of(props: &[Proposition]) -> Vec<char> {
+    let atoms: BTreeSet<char> = props.iter().flat_map(atoms).collect();
+    atoms.into_iter().collect()
+}
+
+// Every assignment to `atoms`, in the row order of `TruthTable`.
+pub(crate) fn assignments(atoms: &[char]) -> impl Iterator<Item = HashMap<char, bool>> + '_ {
+    counting(atoms.len()).map(move |values| atoms.iter().copied().zip(values).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    fn formulas(sources: &[&str]) -> Vec<Proposition> {
+        sources.iter().map(|source| parse_formula(source).unwrap()).collect()
+    }
+
+    #[test]
+    fn finds_a_witness_for_a_consistent_set() {
+        let props = formulas(&["P → Q", "Q → R", "P ∨ S", "¬S"]);
+        assert!(is_consistent(&props));
+        let model = consistent_model(&props).unwrap();
+        assert_eq!(model, HashMap::from([('P', true), ('Q', true), ('R', true), ('S', false)]));
+    }
+
+    #[test]
+    fn rejects_contradictory_sets() {
+        let props = formulas(&["P", "¬P"]);
+        assert!(!is_consistent(&props));
+        assert_eq!(consistent_model(&props), None);
+        assert!(is_consistent(&[]));
+    }
+
+    #[test]
+    fn solves_horn_formulas_like_brute_force() {
+        let satisfiable = parse_formula("(P → (Q → R)) ∧ (S → P) ∧ S ∧ (Q ∨ ¬S) ∧ (T → (R → P))").unwrap();
+        let model = horn_sat(&satisfiable).unwrap().unwrap();
+        assert_eq!(model, HashMap::from([('P', true), ('Q', true), ('R', true), ('S', true), ('T', false)]));
+        assert!(eval_rpn(&to_rpn(&satisfiable), &model).unwrap());
+        assert!(is_consistent(&[satisfiable]));
+
+        let unsatisfiable = parse_formula("P ∧ (P → Q) ∧ (Q → R) ∧ ¬(R ∧ P)").unwrap();
+        assert_eq!(horn_sat(&unsatisfiable), Ok(None));
+        assert!(!is_consistent(&[unsatisfiable]));
+    }
+
+    #[test]
+    fn rejects_formulas_outside_the_horn_fragment() {
+        assert_eq!(horn_sat(&parse_formula("P ∨ Q").unwrap()), Err(Error::NotHorn));
+        assert_eq!(horn_sat(&parse_formula("□P").unwrap()), Err(Error::NotHorn));
+    }
+
+    #[test]
+    fn counts_weighted_models() {
+        let prop = parse_formula("P ∨ Q").unwrap();
+        let weights = HashMap::from([(('P', true), 0.3), (('P', false), 0.7), (('Q', true), 0.6), (('Q', false), 0.4)]);
+        // 0.3·0.6 + 0.3·0.4 + 0.7·0.6, or 1 - 0.7·0.4.
+        assert!((weighted_model_count(&prop, &weights) - 0.72).abs() < 1e-12);
+        assert_eq!(weighted_model_count(&prop, &HashMap::new()), 3.0);
+        assert_eq!(model_count(&prop), 3);
+        assert_eq!(model_count(&parse_formula("P ∧ ¬P").unwrap()), 0);
+    }
+
+    #[test]
+    fn measures_output_entropy() {
+        assert_eq!(output_entropy(&parse_formula("P").unwrap()), 1.0);
+        assert_eq!(output_entropy(&parse_formula("P ∧ ¬P").unwrap()), 0.0);
+        assert_eq!(output_entropy(&parse_formula("P ∨ ¬P").unwrap()), 0.0);
+        assert_eq!(output_entropy(&parse_formula("P ↔ Q").unwrap()), 1.0);
+        // True on one assignment in four.
+        let entropy = output_entropy(&parse_formula("P ∧ Q").unwrap());
+        assert!((entropy - (2.0 - 0.75 * 3f64.log2())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bounds_probabilities_from_intervals() {
+        let close = |(lo, hi): (f64, f64), (expected_lo, expected_hi): (f64, f64)| (lo - expected_lo).abs() < 1e-12 && (hi - expected_hi).abs() < 1e-12;
+        let intervals = HashMap::from([('P', (0.2, 0.5)), ('Q', (0.4, 0.6))]);
+        assert!(close(probability_interval(&parse_formula("P ∧ Q").unwrap(), &intervals), (0.08, 0.3)));
+        assert!(close(probability_interval(&parse_formula("P ∨ Q").unwrap(), &intervals), (0.52, 0.8)));
+        assert!(close(probability_interval(&parse_formula("P ∨ ¬P").unwrap(), &intervals), (1.0, 1.0)));
+        assert!(close(probability_interval(&parse_formula("P ∧ R").unwrap(), &intervals), (0.0, 0.5)));
+    }
+
+    #[test]
+    fn checks_equivalence() {
+        assert!(equivalent(&parse_formula("P → Q").unwrap(), &parse_formula("¬P ∨ Q").unwrap()));
+        assert!(!equivalent(&parse_formula("P → Q").unwrap(), &parse_formula("Q → P").unwrap()));
+    }
+
+    #[test]
+    fn finds_redundant_premises() {
+        let premises = formulas(&["P → Q", "Q ∨ ¬R", "P"]);
+        let conclusion = parse_formula("Q").unwrap();
+        assert!(entails(&premises, &conclusion));
+        assert_eq!(redundant_premises(&premises, &conclusion), vec![1]);
+        assert_eq!(redundant_premises(&formulas(&["P", "P"]), &conclusion), vec![0, 1]);
+    }
+
+    #[test]
+    fn extracts_the_conflicting_formulas() {
+        let props = formulas(&["P ∨ Q", "P → R", "¬Q", "¬R"]);
+        assert_eq!(minimal_unsat_subset(&props), vec![0, 1, 2, 3]);
+        let props = formulas(&["P", "P → Q", "¬P"]);
+        assert_eq!(minimal_unsat_subset(&props), vec![0, 2]);
+        assert_eq!(minimal_unsat_subset(&formulas(&["P", "Q"])), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::BTreeSet;
+
+use crate::random::Rng;
+use crate::truth_table::counting;
+use crate::{bisimulation_contraction, evaluate_at, Atom, Model, Proposition, World};
+
+/// The frames a validity search ranges over, each up to the search's world bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFamily {
+    /// Every accessibility relation. There are 2^(n²) on n worlds, so keep the bound to 3 or so.
+    All,
+    /// Strict linear orders `0 < 1 < … < n-1`, the frames of future-tense temporal logic.
+    LinearOrders,
+    /// Trees rooted at world 0, with edges from each parent to its children.
+    Trees,
+    /// Rectangular grids with an edge to the right of and below each cell, row by row from world 0.
+    Grids,
+    /// The frames of a normal modal system, generated a pair of worlds at a time, dropping each partial
+    /// relation as soon as it breaks the system's condition.
+    System(System),
+}
+
+/// Normal modal systems, each characterised by a condition on the accessibility relation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+    /// Every frame.
+    K,
+    /// Serial: every world accesses some world.
+    D,
+    /// Reflexive.
+    T,
+    /// Reflexive and symmetric.
+    B,
+    /// Transitive.
+    K4,
+    /// Reflexive and transitive.
+    S4,
+    /// Reflexive and euclidean, i.e. an equivalence relation.
+    S5,
+}
+
+impl System {
+    /// Whether the frame on `worlds` with the given edges satisfies the system's condition.
+    pub fn admits(&self, worlds: &[World], edges: &[(World, World)]) -> bool {
+        let related = |from: World, to: World| edges.contains(&(from, to));
+        let serial = || worlds.iter().all(|world| edges.iter().any(|(from, _)| from == world));
+        let reflexive = || worlds.iter().all(|world| related(*world, *world));
+        let symmetric = || edges.iter().all(|(from, to)| related(*to, *from));
+        let transitive = || edges.iter().all(|(from, middle)| edges.iter().filter(|(start, _)| start == middle).all(|(_, to)| related(*from, *to)));
+        let euclidean = || edges.iter().all(|(from, to)| edges.iter().filter(|(start, _)| start == from).all(|(_, other)| related(*to, *other)));
+        match self {
+            System::K => true,
+            System::D => serial(),
+            System::T => reflexive(),
+            System::B => reflexive() && symmetric(),
+            System::K4 => transitive(),
+            System::S4 => reflexive() && transitive(),
+            System::S5 => reflexive() && euclidean(),
+        }
+    }
+}
+
+impl Model {
+    /// This model with the fewest edges added to put its frame in `system`: the reflexive, symmetric
+    /// and transitive closures its condition calls for. For `System::D` each world that sees nothing is
+    /// made to see itself.
+    pub fn close_under(&self, system: System) -> Model {
+        let (reflexive, symmetric, transitive) = match system {
+            System::K | System::D => (false, false, false),
+            System::T => (true, false, false),
+            System::B => (true, true, false),
+            System::K4 => (false, false, true),
+            System::S4 => (true, false, true),
+            System::S5 => (true, true, true),
+        };
+        let mut closed = self.clone();
+        let worlds: Vec<World> = self.worlds().collect();
+        for world in &worlds {
+            if reflexive || (system == System::D && closed.successors(*world).next().is_none()) {
+                closed.add_edge(*world, *world);
+            }
+        }
+        if symmetric {
+            for (from, to) in edges(&closed) {
+                closed.add_edge(to, from);
+            }
+        }
+        if transitive {
+            for world in &worlds {
+                let reachable: Vec<World> = closed.successors(*world).flat_map(|next| closed.reachable_from(next)).collect();
+                for next in reachable {
+                    closed.add_edge(*world, next);
+                }
+            }
+        }
+        closed
+    }
+}
+
+/// Whether `prop` holds at every world of every model on a frame of `family` with at most `max_worlds`
+/// worlds. This is bounded: `true` only means no countermodel of that size exists.
+pub fn is_valid_in(prop: &Proposition, family: FrameFamily, max_worlds: usize) -> bool {
+    find_countermodel(prop, family, max_worlds).is_none()
+}
+
+/// Whether `left ↔ right` holds throughout every model on a frame of `system` with at most `max_worlds`
+/// worlds. Like `is_valid_in` this is bounded: `true` only means no model that size tells the two
+/// apart, though three worlds are enough for the usual collapses such as `□P ↔ □□P` in S4.
+pub fn modally_equivalent(system: System, left: &Proposition, right: &Proposition, max_worlds: usize) -> bool {
+    is_valid_in(&Proposition::iff(left.clone(), right.clone()), FrameFamily::System(system), max_worlds)
+}
+
+/// A model on a frame of `family` with at most `max_worlds` worlds, and a world of it where `prop` is
+/// false. Frames are tried smallest first, so the countermodel found is one of the smallest. Every
+/// valuation of the formula's atoms is tried on each frame, and every naming of its nominals.
+pub fn find_countermodel(prop: &Proposition, family: FrameFamily, max_worlds: usize) -> Option<(Model, World)> {
+    find_model(std::slice::from_ref(prop), family, max_worlds, |model| model.worlds().find(|world| !evaluate_at(model, *world, prop)))
+}
+
+/// Looks for a countermodel to `prop` among `attempts` models drawn at random, each on a frame of
+/// `family` with up to `max_worlds` worlds and with a random valuation and naming, for frames too big
+/// to search through as `find_countermodel` does. The draws are seeded with `seed`, so a seed always
+/// finds the same countermodel, or none. Frames of a `System` are drawn as random relations closed
+/// under it. With no worlds allowed there's nothing to draw, and no countermodel.
+pub fn sample_countermodel(prop: &Proposition, family: FrameFamily, max_worlds: usize, attempts: usize, seed: u64) -> Option<(Model, World)> {
+    if max_worlds == 0 {
+        return None;
+    }
+    let mut rng = Rng::new(seed);
+    let (variables, nominals) = signature(std::slice::from_ref(prop));
+    (0..attempts).find_map(|_| {
+        let worlds = 1 + rng.below(max_worlds);
+        let mut model = Model::new();
+        for world in 0..worlds {
+            let true_atoms: Vec<char> = variables.iter().copied().filter(|_| rng.coin()).collect();
+            model.add_world(world, &true_atoms);
+        }
+        for (from, to) in random_frame(family, worlds, &mut rng) {
+            model.add_edge(from, to);
+        }
+        for nominal in &nominals {
+            model.name_world(*nominal, rng.below(worlds));
+        }
+        let world = model.worlds().find(|world| !evaluate_at(&model, *world, prop))?;
+        Some((model, world))
+    })
+}
+
+/// Shrinks a countermodel to `prop` on a frame of `system`, for a clearer witness. It's first contracted
+/// by bisimulation, then worlds and after them edges are dropped one at a time, keeping each removal
+/// that leaves the frame in `system` and `prop` false somewhere. No single world of the result can go.
+/// `None` if `model` isn't a countermodel to `prop` on a frame of `system` to begin with.
+pub fn minimize_countermodel(system: System, prop: &Proposition, model: Model) -> Option<Model> {
+    let falsified = |candidate: &Model| {
+        let worlds: Vec<World> = candidate.worlds().collect();
+        system.admits(&worlds, &edges(candidate)) && worlds.iter().any(|world| !evaluate_at(candidate, *world, prop))
+    };
+    if !falsified(&model) {
+        return None;
+    }
+    let contracted = bisimulation_contraction(&model);
+    let mut model = if falsified(&contracted) { contracted } else { model };
+    loop {
+        let worlds: BTreeSet<World> = model.worlds().collect();
+        let smaller = worlds.iter().map(|world| {
+            let mut kept = worlds.clone();
+            kept.remove(world);
+            model.restrict(&kept)
+        });
+        let sparser = edges(&model).into_iter().map(|(from, to)| {
+            let mut candidate = model.clone();
+            candidate.remove_edge(from, to);
+            candidate
+        });
+        match smaller.chain(sparser).find(|candidate| falsified(candidate)) {
+            Some(candidate) => model = candidate,
+            None => return Some(model),
+        }
+    }
+}
+
+fn edges(model: &Model) -> Frame {
+    model.worlds().flat_map(|from| model.successors(from).map(move |to| (from, to))).collect()
+}
+
+// The first model, over the atoms and nominals of `props`, at which `pick` picks a world.
+pub(crate) fn find_model(props: &[Proposition], family: FrameFamily, max_worlds: usize, pick: impl Fn(&Model) -> Option<World>) -> Option<(Model, World)> {
+    let (variables, nominals) = signature(props);
+    (1..=max_worlds).flat_map(|worlds| frames(family, worlds).map(move |edges| (worlds, edges))).find_map(|(worlds, edges)| {
+        models(worlds, &edges, &variables, &nominals).find_map(|model| {
+            let world = pick(&model)?;
+            Some((model, world))
+        })
+    })
+}
+
+// The atoms and the nominals of `props`.
+pub(crate) fn signature(props: &[Proposition]) -> (Vec<char>, Vec<char>) {
+    let (mut variables, mut nominals) = (BTreeSet::new(), BTreeSet::new());
+    let mut pending: Vec<&Proposition> = props.iter().collect();
+    while let Some(prop) = pending.pop() {
+        match prop {
+            Proposition::Atom(Atom::Variable(name)) => {
+                variables.insert(*name);
+            }
+            Proposition::Atom(Atom::Nominal(name)) => {
+                nominals.insert(*name);
+            }
+            _ => pending.extend(prop.operands()),
+        }
+    }
+    (variables.into_iter().collect(), nominals.into_iter().collect())
+}
+
+type Frame = Vec<(World, World)>;
+
+// A frame of `family` with exactly `worlds` worlds, drawn with `rng`.
+fn random_frame(family: FrameFamily, worlds: usize, rng: &mut Rng) -> Frame {
+    match family {
+        FrameFamily::All => (0..worlds).flat_map(|from| (0..worlds).map(move |to| (from, to))).filter(|_| rng.coin()).collect(),
+        FrameFamily::System(system) => {
+            let mut model = Model::new();
+            for world in 0..worlds {
+                model.add_world(world, &[]);
+            }
+            for (from, to) in random_frame(FrameFamily::All, worlds, rng) {
+                model.add_edge(from, to);
+            }
+            edges(&model.close_under(system))
+        }
+        FrameFamily::Trees => (1..worlds).map(|child| (rng.below(child), child)).collect(),
+        FrameFamily::LinearOrders | FrameFamily::Grids => {
+            let mut frames: Vec<Frame> = frames(family, worlds).collect();
+            frames.swap_remove(rng.below(frames.len()))
+        }
+    }
+}
+
+// The frames of `family` with exactly `worlds` worlds, as their edges, each made as it's needed.
+fn frames(family: FrameFamily, worlds: usize) -> Box<dyn Iterator<Item = Frame>> {
+    match family {
+        FrameFamily::All => relations(System::K, worlds),
+        FrameFamily::System(system) => relations(system, worlds),
+        FrameFamily::LinearOrders => Box::new(std::iter::once((0..worlds).flat_map(|from| (from + 1..worlds).map(move |to| (from, to))).collect())),
+        FrameFamily::Trees => {
+            // Every choice of an earlier parent for each world after the root.
+            let mut trees: Vec<Frame> = vec![Vec::new()];
+            for child in 1..worlds {
+                trees = trees.into_iter().flat_map(|tree| (0..child).map(move |parent| [tree.clone(), vec![(parent, child)]].concat())).collect();
+            }
+            Box::new(trees.into_iter())
+        }
+        // A grid one world wide is the same chain as one a single row long, so it's only listed once.
+        FrameFamily::Grids => Box::new((1..=worlds)
+            .filter(move |width| worlds.is_multiple_of(*width) && (*width < worlds || worlds == 1))
+            .map(move |width| {
+                let cell = |world: World| (world / width, world % width);
+                (0..worlds)
+                    .flat_map(|from| (0..worlds).map(move |to| (from, to)))
+                    .filter(|(from, to)| {
+                        let ((row, column), (to_row, to_column)) = (cell(*from), cell(*to));
+                        (to_row == row && to_column == column + 1) || (to_row == row + 1 && to_column == column)
+                    })
+                    .collect()
+            })),
+    }
+}
+
+// The relations on `worlds` worlds in `system`, deciding whether each pair is related in turn, unrelated
+// first, and dropping a partial relation as soon as the pairs decided rule the system out.
+fn relations(system: System, worlds: usize) -> Box<dyn Iterator<Item = Frame>> {
+    let pairs: Vec<(World, World)> = (0..worlds).flat_map(|from| (0..worlds).map(move |to| (from, to))).collect();
+    let mut pending: Vec<Vec<bool>> = vec![Vec::new()];
+    Box::new(std::iter::from_fn(move || {
+        while let Some(decided) = pending.pop() {
+            if rules_out(system, worlds, &decided) {
+                continue;
+            }
+            if decided.len() == pairs.len() {
+                return Some(pairs.iter().zip(&decided).filter(|(_, related)| **related).map(|(pair, _)| *pair).collect());
+            }
+            pending.extend([true, false].map(|related| [decided.as_slice(), &[related]].concat()));
+        }
+        None
+    }))
+}
+
+// Whether the pairs decided so far, `decided[from * worlds + to]` for the first few, already break
+// the condition of `system` however the rest are decided.
+fn rules_out(system: System, worlds: usize, decided: &[bool]) -> bool {
+    let related = |from: World, to: World| decided.get(from * worlds + to).copied();
+    let all = || 0..worlds;
+    let serial = || all().any(|world| decided.len() >= (world + 1) * worlds && all().all(|to| related(world, to) == Some(false)));
+    let reflexive = || all().any(|world| related(world, world) == Some(false));
+    let symmetric = || all().any(|from| all().any(|to| related(from, to) == Some(true) && related(to, from) == Some(false)));
+    let triples = |broken: &dyn Fn(World, World, World) -> bool| all().any(|x| all().any(|y| all().any(|z| broken(x, y, z))));
+    let transitive = || triples(&|from, middle, to| related(from, middle) == Some(true) && related(middle, to) == Some(true) && related(from, to) == Some(false));
+    let euclidean = || triples(&|from, to, other| related(from, to) == Some(true) && related(from, other) == Some(true) && related(to, other) == Some(false));
+    match system {
+        System::K => false,
+        System::D => serial(),
+        System::T => reflexive(),
+        System::B => reflexive() || symmetric(),
+        System::K4 => transitive(),
+        System::S4 => reflexive() || transitive(),
+        System::S5 => reflexive() || euclidean(),
+    }
+}
+
+// Every model on a frame, over the given atoms and nominals.
+fn models<'a>(worlds: usize, edges: &'a Frame, variables: &'a [char], nominals: &'a [char]) -> impl Iterator<Item = Model> + 'a {
+    let namings = worlds.pow(nominals.len() as u32);
+    // The bit for an atom at a world counts from the end, so the first atom at world 0 changes fastest.
+    counting(worlds * variables.len()).flat_map(move |valuation| {
+        (0..namings).map(move |naming| {
+            let mut model = Model::new();
+            for world in 0..worlds {
+                let true_atoms: Vec<char> = variables.iter().enumerate().filter(|(index, _)| valuation[valuation.len() - 1 - (world * variables.len() + index)]).map(|(_, atom)| *atom).collect();
+                model.add_world(world, &true_atoms);
+            }
+            for (from, to) in edges {
+                model.add_edge(*from, *to);
+            }
+            for (index, nominal) in nominals.iter().enumerate() {
+                model.name_world(*nominal, naming / worlds.pow(index as u32) % worlds);
+            }
+            model
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_enriched_formula, parse_formula};
+
+    #[test]
+    fn checks_temporal_formulas_over_linear_orders() {
+        let transitivity = parse_formula("(□P) → □□P").unwrap();
+        assert!(is_valid_in(&transitivity, FrameFamily::LinearOrders, 5));
+        assert!(!is_valid_in(&transitivity, FrameFamily::All, 3));
+
+        // Any two future events happen in some order.
+        let connectedness = parse_formula("((◇P) ∧ ◇Q) → ((◇(P ∧ Q)) ∨ (◇(P ∧ ◇Q)) ∨ ◇(Q ∧ ◇P))").unwrap();
+        assert!(is_valid_in(&connectedness, FrameFamily::LinearOrders, 4));
+        assert!(!is_valid_in(&connectedness, FrameFamily::Trees, 4));
+
+        // Atoms are atoms whatever their case.
+        let lowercase = Proposition::if_then(Proposition::variable('p'), Proposition::necessarily(Proposition::variable('p')));
+        assert!(!is_valid_in(&lowercase, FrameFamily::All, 2));
+
+        let (model, world) = find_countermodel(&parse_formula("(□P) → P").unwrap(), FrameFamily::LinearOrders, 4).unwrap();
+        assert_eq!(model.worlds().count(), 1);
+        assert!(!evaluate_at(&model, world, &parse_formula("P").unwrap()));
+    }
+
+    #[test]
+    fn samples_countermodels_reproducibly() {
+        let axiom_4 = parse_formula("□P → □□P").unwrap();
+        let first = sample_countermodel(&axiom_4, FrameFamily::All, 5, 100, 2024).expect("4 fails on some frame");
+        assert_eq!(sample_countermodel(&axiom_4, FrameFamily::All, 5, 100, 2024), Some(first.clone()));
+        let (model, world) = first;
+        assert!(!evaluate_at(&model, world, &axiom_4));
+
+        // Every draw from a system's frames is in the system, where its axioms hold.
+        assert_eq!(sample_countermodel(&axiom_4, FrameFamily::System(System::S4), 5, 100, 2024), None);
+        assert_eq!(sample_countermodel(&axiom_4, FrameFamily::All, 0, 100, 2024), None);
+        let (model, world) = sample_countermodel(&parse_enriched_formula("◇i → i").unwrap(), FrameFamily::Trees, 4, 100, 1).unwrap();
+        assert_eq!(edges(&model).len(), model.worlds().count() - 1);
+        assert!(!evaluate_at(&model, world, &parse_enriched_formula("◇i → i").unwrap()));
+    }
+
+    #[test]
+    fn enumerates_each_family() {
+        assert_eq!(frames(FrameFamily::All, 2).count(), 16);
+        assert_eq!(frames(FrameFamily::Trees, 4).count(), 6);
+        assert_eq!(frames(FrameFamily::Grids, 4).collect::<Vec<Frame>>(), vec![vec![(0, 1), (1, 2), (2, 3)], vec![(0, 1), (0, 2), (1, 3), (2, 3)]]);
+        assert_eq!(frames(FrameFamily::Grids, 1).collect::<Vec<Frame>>(), vec![vec![]]);
+        assert_eq!(frames(FrameFamily::System(System::S5), 3).count(), 5);
+        // The preorders on four worlds, and the serial relations on three.
+        assert_eq!(frames(FrameFamily::System(System::S4), 4).count(), 355);
+        assert_eq!(frames(FrameFamily::System(System::D), 3).count(), 343);
+        // Too many frames to list, but the first comes straight away.
+        assert_eq!(frames(FrameFamily::All, 9).next(), Some(vec![]));
+        let nominal = parse_enriched_formula("i → ¬D i").unwrap();
+        assert!(is_valid_in(&nominal, FrameFamily::Grids, 4));
+    }
+
+    #[test]
+    fn closes_models_under_systems() {
+        let mut model = Model::new();
+        model.add_edge(0, 1);
+        model.add_edge(1, 2);
+        model.add_world(3, &[]);
+        for system in [System::D, System::T, System::B, System::K4, System::S4, System::S5] {
+            let closed = model.close_under(system);
+            let worlds: Vec<World> = closed.worlds().collect();
+            assert!(system.admits(&worlds, &edges(&closed)), "{:?}", system);
+            assert!(edges(&model).iter().all(|edge| edges(&closed).contains(edge)));
+        }
+        assert_eq!(edges(&model.close_under(System::K4)), vec![(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(edges(&model.close_under(System::D)), vec![(0, 1), (1, 2), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn equivalence_depends_on_the_system() {
+        let (boxed, twice) = (parse_formula("□P").unwrap(), parse_formula("□□P").unwrap());
+        assert!(modally_equivalent(System::S4, &boxed, &twice, 3));
+        assert!(modally_equivalent(System::K4, &boxed, &parse_formula("□P ∧ □□P").unwrap(), 3));
+        assert!(!modally_equivalent(System::K, &boxed, &twice, 3));
+        assert!(!modally_equivalent(System::T, &boxed, &twice, 3));
+        assert!(modally_equivalent(System::S5, &parse_formula("◇P").unwrap(), &parse_formula("□◇P").unwrap(), 3));
+        assert!(!modally_equivalent(System::S4, &parse_formula("◇P").unwrap(), &parse_formula("□◇P").unwrap(), 3));
+
+        // Telling these apart takes a world with three successors, beyond a bound of two worlds.
+        let three_successors = parse_formula("(◇P) ∧ (◇Q) ∧ (◇R) ∧ □(¬(P ∧ Q) ∧ ¬(P ∧ R) ∧ ¬(Q ∧ R))").unwrap();
+        assert!(modally_equivalent(System::K, &three_successors, &Proposition::constant(false), 2));
+        assert!(!modally_equivalent(System::K, &three_successors, &Proposition::constant(false), 3));
+    }
+
+    #[test]
+    fn minimizes_countermodels() {
+        // A chain 0 → 1 → 2 → 3 ending in a loop, with P false only at 3, falsifies the formula at 2 and 3.
+        let prop = parse_formula("□(P ∨ □P)").unwrap();
+        let mut model = Model::new();
+        for world in 0..4 {
+            model.add_world(world, if world == 3 { &[] } else { &['P'] });
+            model.add_edge(world, (world + 1).min(3));
+        }
+        model.add_edge(0, 2);
+        assert_eq!(minimize_countermodel(System::T, &prop, model.clone()), None);
+        let minimized = minimize_countermodel(System::K, &prop, model).unwrap();
+        let worlds: BTreeSet<World> = minimized.worlds().collect();
+        assert!(worlds.iter().any(|world| !evaluate_at(&minimized, *world, &prop)));
+        assert!(worlds.len() < 4);
+        for world in &worlds {
+            let mut kept = worlds.clone();
+            kept.remove(world);
+            let smaller = minimized.restrict(&kept);
+            assert!(smaller.worlds().all(|other| evaluate_at(&smaller, other, &prop)));
+        }
+    }
+
+    #[test]
+    fn minimized_countermodels_stay_in_the_system() {
+        let (model, _) = find_countermodel(&parse_formula("(◇P) → □◇P").unwrap(), FrameFamily::System(System::S4), 3).unwrap();
+        let minimized = minimize_countermodel(System::S4, &parse_formula("(◇P) → □◇P").unwrap(), model).unwrap();
+        let worlds: Vec<World> = minimized.worlds().collect();
+        assert!(System::S4.admits(&worlds, &edges(&minimized)));
+        assert_eq!(worlds.len(), 2);
+    }
+}
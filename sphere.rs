@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::model::{evaluate_in, Semantics};
+use crate::{Model, Proposition, World};
+
+/// A Lewis–Stalnaker sphere model: a `Model` whose worlds each carry a similarity ordering of the
+/// others, against which counterfactuals are evaluated.
+///
+/// Each world is the closest world to itself. It's followed by rings of worlds, closest first, where
+/// the worlds of a ring are equally close; the spheres around the world are the unions of the first
+/// few rings. Worlds in no ring are too remote to be entertained at all.
+#[derive(Debug, Clone, Default)]
+pub struct SphereModel {
+    model: Model,
+    rings: HashMap<World, Vec<Vec<World>>>,
+}
+
+impl SphereModel {
+    /// A sphere model over `model` in which every world is similar only to itself until told otherwise.
+    pub fn new(model: Model) -> SphereModel {
+        SphereModel { model, rings: HashMap::new() }
+    }
+
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Orders the worlds around `world` by similarity to it: `rings[0]` are the closest after `world`
+    /// itself, then `rings[1]`, and so on.
+    pub fn set_similarity(&mut self, world: World, rings: &[&[World]]) {
+        self.rings.insert(world, rings.iter().map(|ring| ring.to_vec()).collect());
+    }
+
+    /// Evaluates `prop` at `world`. `φ □→ ψ` holds if ψ holds at every one of the closest worlds where φ
+    /// does, and vacuously if φ holds nowhere in the spheres; the other operators are as in `evaluate_at`.
+    pub fn evaluate(&self, world: World, prop: &Proposition) -> bool {
+        evaluate_in(self, world, prop)
+    }
+}
+
+impl Semantics for SphereModel {
+    fn model(&self) -> &Model {
+        &self.model
+    }
+
+    fn counterfactual(&self, world: World, antecedent: &Proposition, consequent: &Proposition) -> bool {
+        let own = vec![world];
+        let rings = std::iter::once(&own).chain(self.rings.get(&world).into_iter().flatten());
+        for ring in rings {
+            let closest: Vec<World> = ring.iter().copied().filter(|other| self.evaluate(*other, antecedent)).collect();
+            if !closest.is_empty() {
+                return closest.iter().all(|other| self.evaluate(*other, consequent));
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    // Kangaroos (P) without tails would topple over (Q), in the closest tailless world 1; in the remote
+    // world 2 they're tailless but use crutches.
+    fn kangaroos() -> SphereModel {
+        let mut model = Model::new();
+        model.add_world(0, &[]);
+        model.add_world(1, &['P', 'Q']);
+        model.add_world(2, &['P']);
+        let mut spheres = SphereModel::new(model);
+        spheres.set_similarity(0, &[&[1], &[2]]);
+        spheres.set_similarity(1, &[&[0, 2]]);
+        spheres
+    }
+
+    #[test]
+    fn counterfactuals_look_only_at_the_closest_antecedent_worlds() {
+        let spheres = kangaroos();
+        assert!(spheres.evaluate(0, &parse_formula("P □→ Q").unwrap()));
+        assert!(!spheres.evaluate(0, &parse_formula("P □→ ¬Q").unwrap()));
+        // The material conditional is vacuously true at 0 either way.
+        assert!(spheres.evaluate(0, &parse_formula("P → ¬Q").unwrap()));
+
+        // At 1 the antecedent already holds, so only 1 itself counts.
+        assert!(spheres.evaluate(1, &parse_formula("P □→ Q").unwrap()));
+        assert!(!spheres.evaluate(1, &parse_formula("¬Q □→ P").unwrap()));
+        assert!(spheres.evaluate(2, &parse_formula("Q □→ S").unwrap()));
+    }
+
+    #[test]
+    fn nests_counterfactuals() {
+        let spheres = kangaroos();
+        // From 1, the closest worlds without Q are 0 and 2, which disagree on P.
+        assert!(!spheres.evaluate(0, &parse_formula("P □→ (¬Q □→ P)").unwrap()));
+        assert!(spheres.evaluate(0, &parse_formula("P □→ ¬(¬Q □→ P)").unwrap()));
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeSet;
+
+use crate::{evaluate_at, Model, Proposition, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmodelKind {
+    /// The submodels generated by each world: everything reachable from it.
+    Generated,
+    /// Every non-empty set of worlds, with the edges between them.
+    Arbitrary,
+}
+
+/// Checks whether `prop` keeps its truth value at every world that survives passing from one of
+/// `examples` to one of its submodels of the given `kind`.
+///
+/// This is a heuristic over the examples supplied, not a proof: `false` means a counterexample pair was
+/// found, while `true` only means none of the sampled pairs is one. Arbitrary submodels are enumerated
+/// exhaustively, so examples checked that way should be small.
+pub fn preserved_under_submodels(prop: &Proposition, examples: &[Model], kind: SubmodelKind) -> bool {
+    examples.iter().all(|model| submodels(model, kind).iter().all(|submodel| agrees_with(model, submodel, prop)))
+}
+
+fn submodels(model: &Model, kind: SubmodelKind) -> Vec<Model> {
+    match kind {
+        SubmodelKind::Generated => model.worlds().map(|world| model.generated_submodel(world)).collect(),
+        SubmodelKind::Arbitrary => {
+            let worlds: Vec<World> = model.worlds().collect();
+            (1..1u64 << worlds.len())
+                .map(|mask| {
+                    let kept: BTreeSet<World> = worlds.iter().enumerate().filter(|(index, _)| mask & (1 << index) != 0).map(|(_, world)| *world).collect();
+                    model.restrict(&kept)
+                })
+                .collect()
+        }
+    }
+}
+
+fn agrees_with(model: &Model, submodel: &Model, prop: &Proposition) -> bool {
+    submodel.worlds().all(|world| evaluate_at(model, world, prop) == evaluate_at(submodel, world, prop))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn examples() -> Vec<Model> {
+        let mut disconnected = Model::new();
+        disconnected.add_world(0, &['P']);
+        disconnected.add_world(1, &[]);
+        disconnected.add_edge(0, 0);
+
+        let mut chain = Model::new();
+        chain.add_world(0, &['Q']);
+        chain.add_world(2, &['P', 'Q']);
+        chain.add_edge(0, 1);
+        chain.add_edge(1, 2);
+        chain.add_edge(2, 0);
+
+        vec![disconnected, chain]
+    }
+
+    #[test]
+    fn modal_formulas_are_preserved_under_generated_submodels() {
+        let standard = Proposition::if_then(Proposition::possibly(Proposition::variable('P')), Proposition::necessarily(Proposition::variable('Q')));
+        let universal = Proposition::everywhere(Proposition::variable('P'));
+        assert!(preserved_under_submodels(&standard, &examples(), SubmodelKind::Generated));
+        assert!(!preserved_under_submodels(&universal, &examples(), SubmodelKind::Generated));
+    }
+
+    #[test]
+    fn modal_formulas_need_not_survive_arbitrary_submodels() {
+        let possibly_p = Proposition::possibly(Proposition::variable('P'));
+        assert!(!preserved_under_submodels(&possibly_p, &examples(), SubmodelKind::Arbitrary));
+        assert!(preserved_under_submodels(&Proposition::variable('P'), &examples(), SubmodelKind::Arbitrary));
+    }
+}
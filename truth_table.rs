@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::{atoms, eval_rpn, to_rpn, AtomOrder, Proposition, Token};
+
+/// The truth table of a formula over its atoms, with a column per atom. Rows are computed on request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable {
+    atoms: Vec<char>,
+    formula: Proposition,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// The value of each atom, in column order.
+    pub values: Vec<bool>,
+    pub result: bool,
+}
+
+impl TruthTable {
+    /// A truth table with its atoms in sorted order.
+    pub fn new(prop: &Proposition) -> TruthTable {
+        TruthTable::with_order(prop, &AtomOrder::Sorted)
+    }
+
+    pub fn with_order(prop: &Proposition, order: &AtomOrder) -> TruthTable {
+        TruthTable { atoms: order.arrange(&atoms(prop)), formula: prop.clone(), tokens: to_rpn(prop) }
+    }
+
+    pub fn atoms(&self) -> &[char] {
+        &self.atoms
+    }
+
+    pub fn formula(&self) -> &Proposition {
+        &self.formula
+    }
+
+    /// Every row, counting up in binary from all-false with the first atom as the most significant bit.
+    pub fn rows(&self) -> Vec<Row> {
+        self.rows_iter().collect()
+    }
+
+    /// The rows in the same order as `rows`, each computed only when it's reached, so a wide table can be
+    /// streamed or abandoned early without ever holding all of it.
+    pub fn rows_iter(&self) -> impl Iterator<Item = Row> + '_ {
+        counting(self.atoms.len()).map(|values| self.row(values))
+    }
+
+    fn row(&self, values: Vec<bool>) -> Row {
+        let env: HashMap<char, bool> = self.atoms.iter().copied().zip(values.iter().copied()).collect();
+        let result = eval_rpn(&self.tokens, &env).expect("every atom has a column");
+        Row { values, result }
+    }
+}
+
+// Every sequence of `width` bits, counting up in binary from all-false with the first bit as the most
+// significant, however many bits there are.
+pub(crate) fn counting(width: usize) -> impl Iterator<Item = Vec<bool>> {
+    let mut next = Some(vec![false; width]);
+    std::iter::from_fn(move || {
+        let bits = next.take()?;
+        // The successor sets the last clear bit and clears the ones after it; all-true has none.
+        if let Some(clear) = bits.iter().rposition(|bit| !bit) {
+            let mut successor = bits.clone();
+            successor[clear] = true;
+            successor[clear + 1..].fill(false);
+            next = Some(successor);
+        }
+        Some(bits)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_formula;
+
+    #[test]
+    fn tabulates_every_assignment() {
+        let table = TruthTable::new(&parse_formula("Q → P").unwrap());
+        assert_eq!(table.atoms(), &['P', 'Q']);
+        let results: Vec<bool> = table.rows().iter().map(|row| row.result).collect();
+        assert_eq!(results, vec![true, false, true, true]);
+        assert_eq!(table.rows()[2].values, vec![true, false]);
+    }
+
+    #[test]
+    fn computes_rows_lazily() {
+        // A formula over 20 atoms, whose full table would have over a million rows.
+        let atoms: Vec<char> = ('a'..='t').collect();
+        let prop = Proposition::disjunction(atoms.iter().map(|atom| Proposition::variable(*atom)));
+        let table = TruthTable::new(&prop);
+        assert_eq!(table.atoms().len(), 20);
+        let first: Vec<Row> = table.rows_iter().take(3).collect();
+        assert_eq!(first.iter().map(|row| row.result).collect::<Vec<bool>>(), vec![false, true, true]);
+        assert!(first[1].values[19] && !first[1].values[18]);
+        assert!(first[2].values[18] && !first[2].values[19]);
+
+        // Wider than any machine word.
+        let atoms: Vec<char> = ('\u{100}'..).take(70).collect();
+        let table = TruthTable::new(&Proposition::conjunction(atoms.iter().map(|atom| Proposition::variable(*atom))));
+        let rows: Vec<Row> = table.rows_iter().take(4).collect();
+        assert_eq!(rows[3].values.iter().filter(|value| **value).count(), 2);
+        assert!(rows.iter().all(|row| !row.result));
+        assert_eq!(counting(2).collect::<Vec<_>>(), vec![vec![false, false], vec![false, true], vec![true, false], vec![true, true]]);
+        assert_eq!(counting(0).count(), 1);
+    }
+
+    #[test]
+    fn custom_orders_permute_columns_but_not_values() {
+        let prop = parse_formula("P ∧ ¬Q ∨ R").unwrap();
+        let sorted = TruthTable::new(&prop);
+        let custom = TruthTable::with_order(&prop, &AtomOrder::Custom(vec!['R', 'P']));
+        assert_eq!(custom.atoms(), &['R', 'P', 'Q']);
+        assert_ne!(custom.rows(), sorted.rows());
+        for row in custom.rows() {
+            let (r, p, q) = (row.values[0], row.values[1], row.values[2]);
+            let index = (p as usize) << 2 | (q as usize) << 1 | r as usize;
+            assert_eq!(sorted.rows()[index].values, vec![p, q, r]);
+            assert_eq!(sorted.rows()[index].result, row.result);
+        }
+    }
+}